@@ -0,0 +1,51 @@
+// Throughput of the frame loop, which makes one SyroVolcaSample_GetSample call
+// per frame as the SYRO library has no batch variant. These track regressions,
+// the loop itself is no faster than before the benchmarks were added.
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use korg_syro::SyroStream;
+
+// 4 seconds of a 440Hz sine wave at 44.1kHz
+fn sine_wave() -> Vec<i16> {
+    (0..4 * 44100)
+        .map(|i| {
+            let t = i as f32 / 44100.0;
+            ((t * 440.0 * std::f32::consts::TAU).sin() * i16::MAX as f32) as i16
+        })
+        .collect()
+}
+
+fn stream() -> SyroStream {
+    let mut syro_stream = SyroStream::default();
+    syro_stream.add_sample(0, sine_wave(), 44100, None).unwrap();
+    syro_stream
+}
+
+fn generate(c: &mut Criterion) {
+    let frames = stream().generator().unwrap().remaining_frames();
+    let mut group = c.benchmark_group("generate");
+    group.throughput(Throughput::Elements(frames as u64));
+    group.sample_size(10);
+
+    group.bench_function("generate", |b| {
+        b.iter_batched(
+            stream,
+            |s| s.generate().unwrap(),
+            criterion::BatchSize::LargeInput,
+        )
+    });
+    group.bench_function("generator_4096", |b| {
+        b.iter_batched(
+            stream,
+            |s| {
+                let mut generator = s.generator().unwrap();
+                let mut buffer = vec![0; 4096 * 2];
+                while generator.fill(&mut buffer).unwrap() > 0 {}
+            },
+            criterion::BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, generate);
+criterion_main!(benches);
@@ -0,0 +1,89 @@
+//! Waveform analysis for drawing overviews of samples
+use std::time::Duration;
+
+/// Minimum and maximum value of each of `buckets` equal slices of the data
+///
+/// Slices left empty when there are fewer values than buckets are (0, 0).
+pub fn peaks(data: &[i16], buckets: usize) -> Vec<(i16, i16)> {
+    (0..buckets)
+        .map(|i| {
+            let slice = &data[i * data.len() / buckets..(i + 1) * data.len() / buckets];
+            let min = slice.iter().copied().min().unwrap_or(0);
+            let max = slice.iter().copied().max().unwrap_or(0);
+            (min, max)
+        })
+        .collect()
+}
+
+/// Length and level of a sample
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Summary {
+    pub frames: usize,
+    pub duration: Duration,
+    /// Largest absolute value
+    pub peak: u16,
+    /// Root mean square of the values
+    pub rms: f32,
+}
+
+impl Summary {
+    /// Summarizes mono PCM data at the given sample rate
+    pub fn of(data: &[i16], sample_rate: u32) -> Self {
+        let peak = data.iter().map(|v| v.unsigned_abs()).max().unwrap_or(0);
+        let sum: f64 = data.iter().map(|&v| (v as f64).powi(2)).sum();
+        let rms = if data.is_empty() {
+            0.0
+        } else {
+            (sum / data.len() as f64).sqrt() as f32
+        };
+        let duration = if sample_rate == 0 {
+            Duration::default()
+        } else {
+            Duration::from_secs_f64(data.len() as f64 / sample_rate as f64)
+        };
+        Self {
+            frames: data.len(),
+            duration,
+            peak,
+            rms,
+        }
+    }
+
+    /// Peak level relative to full scale, negative infinity for silence
+    pub fn peak_dbfs(&self) -> f32 {
+        dbfs(self.peak as f32)
+    }
+
+    /// RMS level relative to full scale, negative infinity for silence
+    pub fn rms_dbfs(&self) -> f32 {
+        dbfs(self.rms)
+    }
+}
+
+fn dbfs(value: f32) -> f32 {
+    20.0 * (value / 32768.0).log10()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_peaks() {
+        let data = [0, 10, -5, 3, 7, -20, 1];
+        assert_eq!(peaks(&data, 3), vec![(0, 10), (-5, 3), (-20, 7)]);
+        assert_eq!(peaks(&data[..2], 4), vec![(0, 0), (0, 0), (0, 0), (10, 10)]);
+        assert!(peaks(&data, 0).is_empty());
+    }
+
+    #[test]
+    fn test_summary() {
+        let summary = Summary::of(&[16384, -16384, 16384, i16::MIN], 4);
+        assert_eq!(summary.frames, 4);
+        assert_eq!(summary.duration, Duration::from_secs(1));
+        assert_eq!(summary.peak, 32768);
+        assert_eq!(summary.peak_dbfs(), 0.0);
+        assert!((summary.rms - 21673.0).abs() < 1.0);
+        assert_eq!(Summary::of(&[], 44100).rms_dbfs(), f32::NEG_INFINITY);
+    }
+}
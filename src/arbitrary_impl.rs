@@ -0,0 +1,96 @@
+//! Arbitrary support for the stream settings, generating only values the
+//! setters accept
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+use crate::*;
+
+impl<'a> Arbitrary<'a> for RatePolicy {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(*u.choose(&[
+            RatePolicy::Strict,
+            RatePolicy::Resample,
+            RatePolicy::Standard,
+            RatePolicy::Passthrough,
+        ])?)
+    }
+}
+
+impl<'a> Arbitrary<'a> for OutputRate {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(match u.int_in_range(0..=2)? {
+            0 => OutputRate::Hz44100,
+            1 => OutputRate::Hz48000,
+            _ => OutputRate::Custom(u.int_in_range(OUTPUT_RATE_RANGE.0..=OUTPUT_RATE_RANGE.1)?),
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for Padding {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut millis =
+            |max| -> Result<Duration> { Ok(Duration::from_millis(u.int_in_range(0..=max)?)) };
+        Ok(Padding {
+            lead_in: millis(2000)?,
+            lead_out: millis(2000)?,
+            ramp: millis(20)?,
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for Calibration {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut millis =
+            |max| -> Result<Duration> { Ok(Duration::from_millis(u.int_in_range(0..=max)?)) };
+        Ok(Calibration {
+            tone: millis(5000)?,
+            level_check: millis(5000)?,
+            gap: millis(2000)?,
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for AddSampleOptions {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let (lo, hi) = BIT_DEPTH_RANGE;
+        Ok(AddSampleOptions {
+            sample_rate: u.int_in_range(MIN_SAMPLE_RATE..=MAX_SAMPLE_RATE)?,
+            compression: match u.arbitrary()? {
+                true => Some(u.int_in_range(lo as u32..=hi as u32)?),
+                false => None,
+            },
+            dither: u.arbitrary()?,
+            normalize: u.arbitrary()?,
+            high_pass: match u.arbitrary()? {
+                true => Some(u.int_in_range(HIGH_PASS_RANGE.0..=HIGH_PASS_RANGE.1)?),
+                false => None,
+            },
+            #[cfg(feature = "stretch")]
+            stretch: match u.arbitrary()? {
+                true => Some(Stretch::Factor(u.int_in_range(50..=200)? as f32 / 100.0)),
+                false => None,
+            },
+            rate_policy: u.arbitrary()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arbitrary_settings() -> anyhow::Result<()> {
+        let mut rng = dsp::Rng(3);
+        for _ in 0..32 {
+            let bytes: Vec<u8> = (0..64).map(|_| rng.next_u64() as u8).collect();
+            let mut u = Unstructured::new(&bytes);
+            let mut syro_stream = SyroStream::default();
+            syro_stream
+                .output_rate(u.arbitrary()?)?
+                .padding(u.arbitrary()?)
+                .calibration(u.arbitrary()?)
+                .add_sample_with(0, vec![1000; 2000], &u.arbitrary()?)?;
+        }
+        Ok(())
+    }
+}
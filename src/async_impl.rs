@@ -0,0 +1,92 @@
+//! Generation on the tokio blocking thread pool
+use tokio::sync::mpsc;
+use tokio::task;
+
+use crate::{SyroError, SyroStream};
+
+impl SyroStream {
+    /// Generates the syro stream on the tokio blocking thread pool
+    ///
+    /// Same as [generate](SyroStream::generate) but does not block the async
+    /// runtime. Must be called from within a tokio runtime.
+    pub async fn generate_async(self) -> Result<Vec<i16>, SyroError> {
+        join(task::spawn_blocking(move || self.generate())).await
+    }
+
+    /// Generates the syro stream on the tokio blocking thread pool, sending the
+    /// PCM data in chunks of at most `chunk_frames` frames
+    ///
+    /// Each chunk holds interleaved stereo samples. Generation stops early if
+    /// the receiver is dropped, and an error ends the sequence of chunks. Must
+    /// be called from within a tokio runtime.
+    pub fn generate_chunks_async(
+        self,
+        chunk_frames: usize,
+    ) -> mpsc::Receiver<Result<Vec<i16>, SyroError>> {
+        let (sender, receiver) = mpsc::channel(4);
+        task::spawn_blocking(move || {
+            if let Err(err) = send_chunks(self, chunk_frames, &sender) {
+                let _ = sender.blocking_send(Err(err));
+            }
+        });
+        receiver
+    }
+}
+
+fn send_chunks(
+    stream: SyroStream,
+    chunk_frames: usize,
+    sender: &mpsc::Sender<Result<Vec<i16>, SyroError>>,
+) -> Result<(), SyroError> {
+    let mut generator = stream.generator()?;
+    while !generator.is_finished() {
+        let mut chunk = vec![0; generator.remaining_frames().min(chunk_frames.max(1)) * 2];
+        generator.fill(&mut chunk)?;
+        if sender.blocking_send(Ok(chunk)).is_err() {
+            break;
+        }
+    }
+    generator.end()
+}
+
+async fn join<T>(handle: task::JoinHandle<Result<T, SyroError>>) -> Result<T, SyroError> {
+    match handle.await {
+        Ok(result) => result,
+        Err(err) if err.is_panic() => std::panic::resume_unwind(err.into_panic()),
+        Err(_) => Err(SyroError::Cancelled),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pattern::Pattern;
+    use crate::SyroStream;
+
+    fn stream() -> anyhow::Result<SyroStream> {
+        let mut syro_stream = SyroStream::default();
+        syro_stream
+            .add_sample(0, vec![0; 4410], 44100, None)?
+            .add_pattern(0, Pattern::default())?;
+        Ok(syro_stream)
+    }
+
+    #[tokio::test]
+    async fn generate_async() -> anyhow::Result<()> {
+        let output = stream()?.generate_async().await?;
+        assert_eq!(output, stream()?.generate()?);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn generate_chunks_async() -> anyhow::Result<()> {
+        let mut receiver = stream()?.generate_chunks_async(1000);
+        let mut output = vec![];
+        while let Some(chunk) = receiver.recv().await {
+            let chunk = chunk?;
+            assert!(chunk.len() <= 2000);
+            output.extend(chunk);
+        }
+        assert_eq!(output, stream()?.generate()?);
+        Ok(())
+    }
+}
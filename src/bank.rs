@@ -0,0 +1,221 @@
+//! Switching the device between kits of samples and patterns
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+
+use crate::pattern::Pattern;
+use crate::{
+    AddSampleOptions, DeviceProfile, PatternIndex, SampleIndex, SyroError, SyroStream, VolcaSample,
+};
+
+#[derive(Clone, Debug, PartialEq)]
+struct KitSample {
+    data: Vec<i16>,
+    options: AddSampleOptions,
+}
+
+/// A complete layout of sample and pattern slots
+///
+/// Slots without a sample are erased when switching to the kit, slots
+/// without a pattern are left as they are.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Kit {
+    samples: BTreeMap<u32, KitSample>,
+    patterns: BTreeMap<usize, Pattern>,
+}
+
+impl Kit {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Puts a sample in the slot, see [add_sample_with](SyroStream::add_sample_with)
+    pub fn sample(
+        &mut self,
+        index: u32,
+        data: Vec<i16>,
+        options: &AddSampleOptions,
+    ) -> Result<&mut Self, SyroError> {
+        SampleIndex::try_from(index)?;
+        let options = *options;
+        self.samples.insert(index, KitSample { data, options });
+        Ok(self)
+    }
+
+    /// Puts a pattern in the slot, in the range 0-9
+    pub fn pattern(&mut self, index: usize, pattern: Pattern) -> Result<&mut Self, SyroError> {
+        PatternIndex::try_from(index)?;
+        self.patterns.insert(index, pattern);
+        Ok(self)
+    }
+
+    /// Returns the occupied sample slots in order
+    pub fn sample_slots(&self) -> impl Iterator<Item = u32> + '_ {
+        self.samples.keys().copied()
+    }
+}
+
+/// The transfer that switches the device to another kit, see
+/// [BankManager::switch_to]
+pub struct BankSwitch {
+    /// Name of the kit the device holds after the transfer
+    pub kit: String,
+    pub stream: SyroStream,
+    /// Sample slots that are loaded
+    pub loads: Vec<u32>,
+    /// Sample slots that are erased
+    pub erases: Vec<u32>,
+    /// Pattern slots that are loaded
+    pub patterns: Vec<usize>,
+}
+
+impl BankSwitch {
+    /// Returns true if the device already holds the kit
+    pub fn is_empty(&self) -> bool {
+        self.loads.is_empty() && self.erases.is_empty() && self.patterns.is_empty()
+    }
+}
+
+/// Holds named kits and tracks which one is assumed to be on the device
+///
+/// Switching only transfers the slots that differ from the kit on the device.
+/// Call [mark_active](BankManager::mark_active) once a transfer has been
+/// played back, so the next switch starts from the new kit.
+#[derive(Clone, Debug, Default)]
+pub struct BankManager {
+    kits: BTreeMap<String, Kit>,
+    // name and contents of the kit last marked as active
+    device: Option<(String, Kit)>,
+}
+
+impl BankManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a kit, replacing a kit with the same name
+    pub fn add_kit<S: Into<String>>(&mut self, name: S, kit: Kit) -> &mut Self {
+        self.kits.insert(name.into(), kit);
+        self
+    }
+
+    pub fn kit(&self, name: &str) -> Option<&Kit> {
+        self.kits.get(name)
+    }
+
+    /// Returns the names of the kits in order
+    pub fn kit_names(&self) -> impl Iterator<Item = &str> + '_ {
+        self.kits.keys().map(String::as_str)
+    }
+
+    /// Returns the name of the kit assumed to be on the device, None when the
+    /// contents of the device are unknown
+    pub fn active(&self) -> Option<&str> {
+        self.device.as_ref().map(|(name, _)| name.as_str())
+    }
+
+    /// Records that the device holds the kit, e.g. after a switch was played
+    /// back or when the kit was loaded by other means
+    pub fn mark_active(&mut self, name: &str) -> Result<&mut Self, SyroError> {
+        let kit = self.get(name)?.clone();
+        self.device = Some((name.to_string(), kit));
+        Ok(self)
+    }
+
+    /// Forgets what is on the device, the next switch transfers a full kit
+    pub fn reset(&mut self) -> &mut Self {
+        self.device = None;
+        self
+    }
+
+    /// Builds the transfer that switches the device to the kit
+    ///
+    /// Samples that are the same in the active kit are skipped. When the
+    /// contents of the device are unknown every sample slot is loaded or
+    /// erased.
+    pub fn switch_to(&self, name: &str) -> Result<BankSwitch, SyroError> {
+        let target = self.get(name)?;
+        let current = self.device.as_ref().map(|(_, kit)| kit);
+        let mut switch = BankSwitch {
+            kit: name.to_string(),
+            stream: SyroStream::default(),
+            loads: vec![],
+            erases: vec![],
+            patterns: vec![],
+        };
+
+        for index in 0..VolcaSample::SAMPLE_SLOTS as u32 {
+            let on_device = current.map(|kit| kit.samples.get(&index));
+            match (target.samples.get(&index), on_device) {
+                (Some(sample), Some(Some(existing))) if sample == existing => {}
+                (Some(sample), _) => {
+                    switch
+                        .stream
+                        .add_sample_with(index, sample.data.clone(), &sample.options)?;
+                    switch.loads.push(index);
+                }
+                (None, Some(None)) => {}
+                (None, _) => {
+                    switch.stream.erase_sample(index)?;
+                    switch.erases.push(index);
+                }
+            }
+        }
+
+        for (&index, pattern) in target.patterns.iter() {
+            if current.and_then(|kit| kit.patterns.get(&index)) != Some(pattern) {
+                switch.stream.add_pattern(index, pattern.clone())?;
+                switch.patterns.push(index);
+            }
+        }
+        Ok(switch)
+    }
+
+    fn get(&self, name: &str) -> Result<&Kit, SyroError> {
+        self.kits.get(name).ok_or_else(|| SyroError::UnknownKit {
+            name: name.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn switch_kits() -> anyhow::Result<()> {
+        let options = AddSampleOptions::new(31250);
+        let mut drums = Kit::new();
+        drums
+            .sample(0, vec![1; 4], &options)?
+            .sample(1, vec![2; 4], &options)?
+            .pattern(0, Pattern::default())?;
+        let mut bass = Kit::new();
+        bass.sample(0, vec![1; 4], &options)?
+            .sample(2, vec![3; 4], &options)?;
+
+        let mut manager = BankManager::new();
+        manager.add_kit("drums", drums).add_kit("bass", bass);
+        assert_eq!(manager.kit_names().collect::<Vec<_>>(), ["bass", "drums"]);
+
+        // nothing is known about the device yet
+        let switch = manager.switch_to("drums")?;
+        assert_eq!(switch.loads, [0, 1]);
+        assert_eq!(switch.erases.len(), 98);
+        assert_eq!(switch.patterns, [0]);
+        switch.stream.generate()?;
+
+        manager.mark_active("drums")?;
+        assert_eq!(manager.active(), Some("drums"));
+        let switch = manager.switch_to("bass")?;
+        assert_eq!(switch.loads, [2]);
+        assert_eq!(switch.erases, [1]);
+        assert!(switch.patterns.is_empty());
+        assert!(manager.switch_to("drums")?.is_empty());
+
+        assert!(matches!(
+            manager.switch_to("keys"),
+            Err(SyroError::UnknownKit { name }) if name == "keys"
+        ));
+        Ok(())
+    }
+}
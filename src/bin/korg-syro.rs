@@ -0,0 +1,186 @@
+//! Command line interface for creating syro streams without writing Rust
+use std::path::{Path, PathBuf};
+
+use clap::{Parser, Subcommand};
+
+use korg_syro::project::{Project, ProjectError, SlotMapping};
+use korg_syro::{AddSampleOptions, SyroStream};
+
+#[derive(Parser)]
+#[command(name = "korg-syro", version, about)]
+struct Cli {
+    /// Output .wav file to write the syro stream to
+    #[arg(short, long, global = true, default_value = "out.wav")]
+    output: PathBuf,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Load every .wav file in a directory into consecutive sample slots, in file name order
+    Encode {
+        dir: PathBuf,
+        /// Slot of the first sample
+        #[arg(long, default_value_t = 0)]
+        start: u32,
+        /// Compression bit depth, 8-16
+        #[arg(short, long)]
+        compression: Option<u32>,
+    },
+    /// Load every .wav file in a directory into the slot its name starts with,
+    /// such as "07 kick.wav" into slot 7
+    Kit {
+        dir: PathBuf,
+        /// Compression bit depth, 8-16
+        #[arg(short, long)]
+        compression: Option<u32>,
+        /// Print the mapping of files to slots without writing the output
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Erase sample slots, given as single slots or ranges such as 0..10 or 0..=99
+    ///
+    /// As in Rust, `..` excludes the end of the range and `..=` includes it.
+    Erase {
+        #[arg(required = true, value_parser = parse_slots)]
+        slots: Vec<Vec<u32>>,
+    },
+    /// Restore a device from an .alldata file
+    Reset {
+        alldata: PathBuf,
+        /// Compression bit depth, 8-16
+        #[arg(short, long)]
+        compression: Option<u32>,
+    },
+    /// Send only the patterns of a project manifest
+    Pattern { manifest: PathBuf },
+    /// Send everything in a project manifest
+    Project { manifest: PathBuf },
+}
+
+const MAX_SLOT: u32 = 99;
+
+fn parse_slots(value: &str) -> Result<Vec<u32>, String> {
+    let parse = |slot: &str| {
+        slot.trim()
+            .parse::<u32>()
+            .map_err(|_| format!("invalid slot '{}'", slot))
+    };
+    match value.split_once("..") {
+        Some((from, to)) => {
+            let empty = || format!("empty range {}", value);
+            let from = parse(from)?;
+            let to = match to.strip_prefix('=') {
+                Some(to) => parse(to)?,
+                None => parse(to)?.checked_sub(1).ok_or_else(empty)?,
+            };
+            if from > to {
+                return Err(empty());
+            }
+            if to > MAX_SLOT {
+                return Err(format!(
+                    "invalid slot {}, expected at most {}",
+                    to, MAX_SLOT
+                ));
+            }
+            Ok((from..=to).collect())
+        }
+        None => Ok(vec![parse(value)?]),
+    }
+}
+
+fn encode(dir: &Path, start: u32, compression: Option<u32>) -> Result<SyroStream, ProjectError> {
+    let mut options = AddSampleOptions::default();
+    if let Some(bit_depth) = compression {
+        options.compression(bit_depth)?;
+    }
+    let mut syro_stream = SyroStream::default();
+    let load = syro_stream.add_dir(dir, start, &options)?;
+    for (path, err) in load.skipped.iter() {
+        eprintln!("skipping {}: {}", path.display(), err);
+    }
+    Ok(syro_stream)
+}
+
+fn run(cli: Cli) -> Result<(), ProjectError> {
+    let Cli { output, command } = cli;
+    let syro_stream = match command {
+        Command::Encode {
+            dir,
+            start,
+            compression,
+        } => encode(&dir, start, compression)?,
+        Command::Kit {
+            dir,
+            compression,
+            dry_run,
+        } => {
+            let mapping = SlotMapping::from_dir(&dir)?;
+            if dry_run {
+                print!("{}", mapping);
+                return Ok(());
+            }
+            let mut project = mapping.to_project()?;
+            for entry in project.samples.iter_mut() {
+                entry.compression = compression;
+            }
+            project.to_syro_stream()?
+        }
+        Command::Erase { slots } => {
+            let mut syro_stream = SyroStream::default();
+            for slot in slots.into_iter().flatten() {
+                syro_stream.erase_sample(slot)?;
+            }
+            syro_stream
+        }
+        Command::Reset {
+            alldata,
+            compression,
+        } => {
+            let input = std::fs::read(&alldata).map_err(|source| ProjectError::Io {
+                path: alldata,
+                source,
+            })?;
+            SyroStream::from_alldata(input, compression)?
+        }
+        Command::Pattern { manifest } => {
+            let project = Project {
+                patterns: Project::load(manifest)?.patterns,
+                ..Default::default()
+            };
+            project.to_syro_stream()?
+        }
+        Command::Project { manifest } => Project::load(manifest)?.to_syro_stream()?,
+    };
+    Ok(syro_stream.generate_to_path(output)?)
+}
+
+fn main() {
+    if let Err(err) = run(Cli::parse()) {
+        eprintln!("error: {}", err);
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slots() {
+        assert_eq!(parse_slots("7"), Ok(vec![7]));
+        assert_eq!(parse_slots("3..5"), Ok(vec![3, 4]));
+        assert_eq!(parse_slots("3..=5"), Ok(vec![3, 4, 5]));
+        assert_eq!(parse_slots("0..100").map(|slots| slots.len()), Ok(100));
+        assert_eq!(parse_slots("0..=99").map(|slots| slots.len()), Ok(100));
+        assert!(parse_slots("5..3").is_err());
+        assert!(parse_slots("5..5").is_err());
+        assert!(parse_slots("0..0").is_err());
+        assert!(parse_slots("0..101").is_err());
+        assert!(parse_slots("0..=100").is_err());
+        assert!(parse_slots("0..4294967295").is_err());
+        assert!(parse_slots("x").is_err());
+    }
+}
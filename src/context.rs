@@ -0,0 +1,108 @@
+//! Reusable buffers for generating many streams in a row
+use byteorder::{ByteOrder, LittleEndian};
+use korg_syro_sys as syro;
+
+use crate::{wav_header, SyroError, SyroStream};
+
+/// Buffers kept between generations, e.g. when rendering many kits in a batch
+///
+/// [generate](GenerationContext::generate) borrows the stream instead of
+/// consuming it and renders into buffers owned by the context, which only
+/// grow when a stream is longer than any generated before.
+#[derive(Default)]
+pub struct GenerationContext {
+    // items of the stream being generated, empty between generations
+    data: Vec<syro::SyroData>,
+    // native rate scratch buffer used when resampling
+    native: Vec<i16>,
+    output: Vec<i16>,
+    wav: Vec<u8>,
+}
+
+// SAFETY: the raw pointers in data are only set while a borrowed stream is
+// generated and the vec is cleared before returning
+unsafe impl Send for GenerationContext {}
+
+impl GenerationContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Generates the syro stream, see [generate](SyroStream::generate)
+    ///
+    /// The returned PCM data stays valid until the next generation.
+    pub fn generate(&mut self, stream: &SyroStream) -> Result<&[i16], SyroError> {
+        let result = stream
+            .fill_syro_data(&mut self.data)
+            .and_then(|_| self.render(stream));
+        self.data.clear();
+        result?;
+        Ok(&self.output)
+    }
+
+    /// Generates the syro stream as the bytes of a WAV file, see
+    /// [generate_wav](SyroStream::generate_wav)
+    pub fn generate_wav(&mut self, stream: &SyroStream) -> Result<&[u8], SyroError> {
+        self.generate(stream)?;
+        let samples = self.output.len();
+        self.wav.clear();
+        self.wav
+            .extend_from_slice(&wav_header(samples, stream.output_rate.hz())?);
+        self.wav.resize(44 + samples * 2, 0);
+        LittleEndian::write_i16_into(&self.output, &mut self.wav[44..]);
+        Ok(&self.wav)
+    }
+
+    fn render(&mut self, stream: &SyroStream) -> Result<(), SyroError> {
+        let native = std::mem::take(&mut self.native);
+        let mut generator = stream.start(&mut self.data, native)?;
+        self.output.clear();
+        self.output.resize(generator.remaining_frames() * 2, 0);
+        generator.fill(&mut self.output)?;
+        generator.end()?;
+        self.native = std::mem::take(&mut generator.native);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{OutputRate, Padding};
+    use std::time::Duration;
+
+    #[test]
+    fn reuse_buffers() -> anyhow::Result<()> {
+        let stream = |value: i16| -> Result<SyroStream, SyroError> {
+            let mut syro_stream = SyroStream::default();
+            syro_stream
+                .add_sample(0, vec![value; 64], 44100, None)?
+                .erase_sample(1)?
+                .output_rate(OutputRate::Hz48000)?
+                .padding(Padding {
+                    lead_in: Duration::from_millis(10),
+                    ..Padding::default()
+                });
+            Ok(syro_stream)
+        };
+
+        let mut context = GenerationContext::new();
+        for value in [1, 2, 3] {
+            let syro_stream = stream(value)?;
+            assert_eq!(
+                context.generate(&syro_stream)?,
+                &stream(value)?.generate()?[..]
+            );
+            assert_eq!(
+                context.generate_wav(&syro_stream)?,
+                &stream(value)?.generate_wav()?[..]
+            );
+        }
+
+        assert_eq!(
+            context.generate(&SyroStream::default()).err(),
+            Some(SyroError::EmptyStream)
+        );
+        Ok(())
+    }
+}
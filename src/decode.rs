@@ -0,0 +1,791 @@
+//! Decoding of syro streams back into the data they transfer
+//!
+//! Demodulates the PCM output of the SYRO library and recovers the samples,
+//! patterns and erase commands it carries, for round-trip tests, debugging
+//! failed transfers and inspecting syro WAV files made with other tools.
+//!
+//! The stream has to be interleaved stereo at [OUTPUT_SAMPLE_RATE](crate::OUTPUT_SAMPLE_RATE), as written
+//! by [generate](crate::SyroStream::generate) with the default output rate.
+//! Silence, a [Calibration](crate::Calibration) passage and a constant
+//! [gain](crate::SyroStream::gain_db) before or around the stream are fine.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use korg_syro::decode::{self, DecodedItem};
+//! use korg_syro::SyroStream;
+//!
+//! let mut syro_stream = SyroStream::default();
+//! syro_stream.erase_sample(42)?;
+//! let items = decode::decode(&syro_stream.generate()?)?;
+//! assert_eq!(items, vec![DecodedItem::Erase { index: 42 }]);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+use std::f32::consts::FRAC_1_SQRT_2;
+
+use byteorder::{BigEndian, ByteOrder, LittleEndian};
+use thiserror::Error;
+
+use crate::{SampleRate, SlotId};
+
+// frames per carrier cycle, each cycle carries 3 bits per channel
+const CYCLE: usize = 8;
+
+// carrier cycle starting at phase 0 and a quarter cycle ahead
+const SIN: [f32; CYCLE] = [
+    0.0,
+    FRAC_1_SQRT_2,
+    1.0,
+    FRAC_1_SQRT_2,
+    0.0,
+    -FRAC_1_SQRT_2,
+    -1.0,
+    -FRAC_1_SQRT_2,
+];
+const COS: [f32; CYCLE] = [
+    1.0,
+    FRAC_1_SQRT_2,
+    0.0,
+    -FRAC_1_SQRT_2,
+    -1.0,
+    -FRAC_1_SQRT_2,
+    0.0,
+    FRAC_1_SQRT_2,
+];
+
+// symbols of the gap between blocks and of the mark starting a block
+const GAP_SYMBOL: u8 = 1;
+const START_MARK_SYMBOL: u8 = 5;
+
+// cycles searched at once for the carrier, and the share of their energy it
+// has to hold
+const CARRIER_WINDOW: usize = 8;
+const CARRIER_PURITY: f32 = 0.9;
+// quieter streams are treated as silence
+const MIN_CARRIER_AMPLITUDE: f32 = 64.0;
+
+const START_CODE: u8 = 0xa9;
+const BLOCK_SIZE: usize = 256;
+const HEADER_SIZE: usize = 32;
+const HEADER_STR: &[u8] = b"KORG SYSTEM FILE";
+const DEVICE_ID: u32 = 0xff00_33b8;
+
+const BLOCK_ALL: u8 = 0x01;
+const BLOCK_ALL_COMPRESS: u8 = 0x03;
+const BLOCK_SAMPLE_LINEAR: u8 = 0x10;
+const BLOCK_PATTERN: u8 = 0x20;
+const BLOCK_SAMPLE_COMPRESS: u8 = 0x30;
+
+// uncompressed head of an .alldata file
+const ALL_INFO_SIZE: usize = 0x4000;
+// playback rate of the device, the header stores sample rates relative to it
+const DEVICE_SAMPLE_RATE: u32 = 31250;
+const SPEED_UNITY: u32 = 0x4000;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum DecodeError {
+    #[error("no syro carrier found in the stream")]
+    NoCarrier,
+
+    #[error("invalid start code {code:#04x} in the block at frame {frame}")]
+    InvalidStartCode { frame: usize, code: u8 },
+
+    #[error("CRC mismatch in the block at frame {frame}")]
+    Crc { frame: usize },
+
+    #[error("data block at frame {frame} does not follow a header")]
+    UnexpectedBlock { frame: usize },
+
+    #[error("unknown device id {id:#010x} in the header at frame {frame}")]
+    UnknownDevice { frame: usize, id: u32 },
+
+    #[error("unknown block code {code:#04x} in the header at frame {frame}")]
+    UnknownBlockCode { frame: usize, code: u8 },
+
+    #[error("the stream ends before all data of the item at frame {frame}")]
+    Truncated { frame: usize },
+
+    #[error("invalid compressed data in the item at frame {frame}")]
+    InvalidCompressedData { frame: usize },
+}
+
+/// An item recovered from a syro stream
+#[derive(Clone, Debug, PartialEq)]
+pub enum DecodedItem {
+    /// Mono PCM data of a sample, `compression` is the bit depth it was
+    /// compressed to, which the data is quantized to as well
+    Sample {
+        index: u8,
+        data: Vec<i16>,
+        sample_rate: u32,
+        compression: Option<u32>,
+    },
+    Erase {
+        index: u8,
+    },
+    /// Raw pattern data, see [Pattern::from_bytes](crate::pattern::Pattern::from_bytes)
+    Pattern {
+        index: u8,
+        data: Vec<u8>,
+    },
+    /// Contents of an .alldata file, see [reset](crate::SyroStream::reset)
+    AllData {
+        data: Vec<u8>,
+        compression: Option<u32>,
+    },
+}
+
+impl DecodedItem {
+    /// The slot the item is transferred to, None for .alldata
+    pub fn slot(&self) -> Option<SlotId> {
+        match self {
+            DecodedItem::Sample { index, .. } | DecodedItem::Erase { index } => {
+                Some(SlotId::Sample(*index))
+            }
+            DecodedItem::Pattern { index, .. } => Some(SlotId::Pattern(*index)),
+            DecodedItem::AllData { .. } => None,
+        }
+    }
+}
+
+/// Decodes the items transferred by a syro stream, in transfer order
+pub fn decode(stream: &[i16]) -> Result<Vec<DecodedItem>, DecodeError> {
+    let mut demodulator = Demodulator::new(stream)?;
+    let mut items = vec![];
+    let mut pending: Option<Pending> = None;
+    while let Some(frame) = demodulator.next_block() {
+        match demodulator.read_block(frame)? {
+            Block::Header(header) => {
+                if let Some(pending) = pending.take() {
+                    items.push(pending.finish()?);
+                }
+                pending = Some(Pending::new(frame, &header)?);
+            }
+            Block::Data(data) => match pending.as_mut() {
+                Some(pending) => pending.data.extend_from_slice(&data),
+                None => return Err(DecodeError::UnexpectedBlock { frame }),
+            },
+        }
+    }
+    if let Some(pending) = pending {
+        items.push(pending.finish()?);
+    }
+    Ok(items)
+}
+
+enum Block {
+    Header([u8; HEADER_SIZE]),
+    Data(Vec<u8>),
+}
+
+// splits the stream into carrier cycles and reads the bits they carry
+struct Demodulator<'a> {
+    stream: &'a [i16],
+    // frame of the next cycle
+    position: usize,
+    // magnitude above which a cycle is at the higher of the two levels
+    threshold: f32,
+    pool: u64,
+    pool_bits: u32,
+}
+
+impl<'a> Demodulator<'a> {
+    fn new(stream: &'a [i16]) -> Result<Self, DecodeError> {
+        let position = find_carrier(stream).ok_or(DecodeError::NoCarrier)?;
+        Ok(Self {
+            stream,
+            position,
+            threshold: 0.0,
+            pool: 0,
+            pool_bits: 0,
+        })
+    }
+
+    // in-phase and quadrature component of a channel in the next cycle
+    fn components(&self, channel: usize) -> Option<(f32, f32)> {
+        let start = (self.position * 2).checked_add(channel)?;
+        let cycle = self.stream.get(start..start + CYCLE * 2 - channel)?;
+        let mut i = 0.0;
+        let mut q = 0.0;
+        for (n, &value) in cycle.iter().step_by(2).enumerate() {
+            i += value as f32 * SIN[n];
+            q += value as f32 * COS[n];
+        }
+        Some((i, q))
+    }
+
+    // symbol of a channel in the next cycle and its magnitude
+    fn symbol(&self, channel: usize) -> Option<(u8, f32)> {
+        let (i, q) = self.components(channel)?;
+        let phase = if i.abs() >= q.abs() {
+            if i >= 0.0 {
+                0
+            } else {
+                2
+            }
+        } else if q >= 0.0 {
+            1
+        } else {
+            3
+        };
+        let magnitude = i.hypot(q);
+        let level = (magnitude > self.threshold) as u8;
+        Some((phase << 1 | level, magnitude))
+    }
+
+    // symbols of both channels in the next cycle, None at the end of the stream
+    fn next_cycle(&mut self) -> Option<(u8, u8)> {
+        let (left, _) = self.symbol(0)?;
+        let (right, _) = self.symbol(1)?;
+        self.position += CYCLE;
+        Some((left, right))
+    }
+
+    // frame of the next start mark following a gap, the channel info cycle
+    // after it is skipped
+    fn next_block(&mut self) -> Option<usize> {
+        let mut gap = false;
+        loop {
+            let (left, left_magnitude) = self.symbol(0)?;
+            let (right, _) = self.symbol(1)?;
+            let frame = self.position;
+            self.position += CYCLE;
+            // gaps are sent at the higher level, which data cycles are measured against
+            if left >> 1 == GAP_SYMBOL >> 1 && right >> 1 == GAP_SYMBOL >> 1 {
+                gap = left_magnitude > MIN_CARRIER_AMPLITUDE;
+                if gap {
+                    self.threshold = left_magnitude / 2.0;
+                }
+            } else if gap && left >> 1 == START_MARK_SYMBOL >> 1 && right >> 1 == left >> 1 {
+                self.next_cycle()?;
+                self.pool = 0;
+                self.pool_bits = 0;
+                return Some(frame);
+            } else {
+                gap = false;
+            }
+        }
+    }
+
+    // the next `bits` bits of the block, least significant first
+    fn read(&mut self, bits: u32, frame: usize) -> Result<u64, DecodeError> {
+        while self.pool_bits < bits {
+            let (left, right) = self.next_cycle().ok_or(DecodeError::Truncated { frame })?;
+            self.pool |= ((right as u64) << 3 | left as u64) << self.pool_bits;
+            self.pool_bits += 6;
+        }
+        let value = self.pool & ((1 << bits) - 1);
+        self.pool >>= bits;
+        self.pool_bits -= bits;
+        Ok(value)
+    }
+
+    fn read_bytes(&mut self, buffer: &mut [u8], frame: usize) -> Result<(), DecodeError> {
+        for byte in buffer.iter_mut() {
+            *byte = self.read(8, frame)? as u8;
+        }
+        Ok(())
+    }
+
+    // reads the block starting at the frame, a header when it holds the
+    // header string and its CRC matches and a data block otherwise
+    fn read_block(&mut self, frame: usize) -> Result<Block, DecodeError> {
+        let code = self.read(8, frame)? as u8;
+        if code != START_CODE {
+            return Err(DecodeError::InvalidStartCode { frame, code });
+        }
+
+        let mut data = vec![0; BLOCK_SIZE];
+        self.read_bytes(&mut data[..HEADER_SIZE], frame)?;
+        let crc = self.read(16, frame)? as u16;
+        if data.starts_with(HEADER_STR) && crc == crc16(&data[..HEADER_SIZE]) {
+            let mut header = [0; HEADER_SIZE];
+            header.copy_from_slice(&data[..HEADER_SIZE]);
+            return Ok(Block::Header(header));
+        }
+
+        // what was read as the CRC of a header is the start of the data
+        LittleEndian::write_u16(&mut data[HEADER_SIZE..HEADER_SIZE + 2], crc);
+        self.read_bytes(&mut data[HEADER_SIZE + 2..], frame)?;
+        // the error correction code is not used, corrupt blocks fail the CRC
+        self.read(24, frame)?;
+        if self.read(16, frame)? as u16 != crc16(&data) {
+            return Err(DecodeError::Crc { frame });
+        }
+        Ok(Block::Data(data))
+    }
+}
+
+// frame of the first carrier cycle, which is part of the gap at the start of
+// the stream
+fn find_carrier(stream: &[i16]) -> Option<usize> {
+    let frames = stream.len() / 2;
+    let window = CARRIER_WINDOW * CYCLE;
+    let mut start = 0;
+    while start + window + CYCLE <= frames {
+        let (i, q, energy) = carrier_energy(stream, start, window);
+        let tone = (i * i + q * q) * 2.0 / window as f32;
+        let amplitude = (2.0 * energy / window as f32).sqrt();
+        if amplitude > MIN_CARRIER_AMPLITUDE && tone > CARRIER_PURITY * energy {
+            // the gap starts at the peak of the in-phase component
+            let offset = (0..CYCLE)
+                .max_by(|&a, &b| {
+                    let (a, _, _) = carrier_energy(stream, start + a, window);
+                    let (b, _, _) = carrier_energy(stream, start + b, window);
+                    a.total_cmp(&b)
+                })
+                .unwrap_or(0);
+            return Some(start + offset);
+        }
+        start += CYCLE;
+    }
+    None
+}
+
+// carrier components and total energy of the left channel over the frames
+fn carrier_energy(stream: &[i16], start: usize, frames: usize) -> (f32, f32, f32) {
+    let mut i = 0.0;
+    let mut q = 0.0;
+    let mut energy = 0.0;
+    for n in 0..frames {
+        let value = stream[(start + n) * 2] as f32;
+        i += value * SIN[n % CYCLE];
+        q += value * COS[n % CYCLE];
+        energy += value * value;
+    }
+    (i, q, energy)
+}
+
+// CRC-16/CCITT as computed by the SYRO library
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc = 0xffff_u16;
+    for &byte in data {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                crc << 1 ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+// an item whose header has been read, collecting its data blocks
+struct Pending {
+    frame: usize,
+    kind: u8,
+    index: u8,
+    size: usize,
+    bit_depth: u32,
+    speed: u16,
+    data: Vec<u8>,
+}
+
+impl Pending {
+    fn new(frame: usize, header: &[u8; HEADER_SIZE]) -> Result<Self, DecodeError> {
+        let id = LittleEndian::read_u32(&header[16..20]);
+        if id != DEVICE_ID {
+            return Err(DecodeError::UnknownDevice { frame, id });
+        }
+        // the lowest bit marks that more items follow, except for .alldata
+        // where it is counted up instead
+        let kind = match header[20] {
+            code @ (BLOCK_ALL | BLOCK_ALL_COMPRESS) => code,
+            code if code == BLOCK_ALL + 1 || code == BLOCK_ALL_COMPRESS + 1 => code - 1,
+            code => match code & !1 {
+                kind @ (BLOCK_SAMPLE_LINEAR | BLOCK_PATTERN | BLOCK_SAMPLE_COMPRESS) => kind,
+                _ => return Err(DecodeError::UnknownBlockCode { frame, code }),
+            },
+        };
+        Ok(Self {
+            frame,
+            kind,
+            index: header[21],
+            size: LittleEndian::read_u32(&header[24..28]) as usize,
+            bit_depth: header[22] as u32,
+            speed: LittleEndian::read_u16(&header[30..32]),
+            data: vec![],
+        })
+    }
+
+    fn finish(mut self) -> Result<DecodedItem, DecodeError> {
+        let frame = self.frame;
+        let compressed = self.kind == BLOCK_SAMPLE_COMPRESS || self.kind == BLOCK_ALL_COMPRESS;
+        if !compressed {
+            if self.data.len() < self.size {
+                return Err(DecodeError::Truncated { frame });
+            }
+            self.data.truncate(self.size);
+        }
+        let compression = if compressed {
+            Some(self.bit_depth)
+        } else {
+            None
+        };
+
+        Ok(match self.kind {
+            BLOCK_SAMPLE_LINEAR if self.size == 0 => DecodedItem::Erase { index: self.index },
+            BLOCK_PATTERN => DecodedItem::Pattern {
+                index: self.index,
+                data: self.data,
+            },
+            BLOCK_SAMPLE_LINEAR | BLOCK_SAMPLE_COMPRESS => {
+                let data = if compressed {
+                    decompress(&self.data, self.size / 2, self.bit_depth, frame)?
+                } else {
+                    let mut data = vec![0; self.size / 2];
+                    LittleEndian::read_i16_into(&self.data[..data.len() * 2], &mut data);
+                    data
+                };
+                DecodedItem::Sample {
+                    index: self.index,
+                    data,
+                    sample_rate: sample_rate(self.speed),
+                    compression,
+                }
+            }
+            _ => {
+                if compressed && self.size > ALL_INFO_SIZE {
+                    let samples = (self.size - ALL_INFO_SIZE) / 2;
+                    let pcm =
+                        decompress(&self.data[ALL_INFO_SIZE..], samples, self.bit_depth, frame)?;
+                    self.data.truncate(ALL_INFO_SIZE);
+                    self.data.resize(ALL_INFO_SIZE + pcm.len() * 2, 0);
+                    LittleEndian::write_i16_into(&pcm, &mut self.data[ALL_INFO_SIZE..]);
+                }
+                DecodedItem::AllData {
+                    data: self.data,
+                    compression,
+                }
+            }
+        })
+    }
+}
+
+/// Value of the speed field in the header of a sample at the given rate
+pub(crate) fn speed(sample_rate: u32) -> u16 {
+    (sample_rate as u64 * SPEED_UNITY as u64 / DEVICE_SAMPLE_RATE as u64) as u16
+}
+
+// sample rate of the speed field, which rounds the rate down so that about
+// two rates share a value, supported rates are recovered exactly and other
+// rates as the lowest rate with the same value
+fn sample_rate(speed: u16) -> u32 {
+    SampleRate::ALL
+        .iter()
+        .map(|rate| rate.hz())
+        .find(|&rate| self::speed(rate) == speed)
+        .unwrap_or_else(|| {
+            let rate = speed as u64 * DEVICE_SAMPLE_RATE as u64;
+            rate.div_ceil(SPEED_UNITY as u64) as u32
+        })
+}
+
+// reads bits most significant first, as written by the compression
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn read(&mut self, bits: u32) -> Option<u32> {
+        let mut value = 0;
+        for _ in 0..bits {
+            let byte = self.data.get(self.position / 8)?;
+            let bit = byte >> (7 - self.position % 8) & 1;
+            value = value << 1 | bit as u32;
+            self.position += 1;
+        }
+        Some(value)
+    }
+}
+
+fn sign_extend(value: u32, bits: u32) -> i32 {
+    let shift = 32 - bits;
+    ((value << shift) as i32) >> shift
+}
+
+// Expands the compressed PCM data of the SYRO library. It is split into blocks
+// of up to 2048 samples, each with a 6 byte head holding the number of samples,
+// the coding, the length of the block and a checksum of the samples.
+fn decompress(
+    data: &[u8],
+    samples: usize,
+    bit_depth: u32,
+    frame: usize,
+) -> Result<Vec<i16>, DecodeError> {
+    if !(8..=16).contains(&bit_depth) {
+        return Err(DecodeError::InvalidCompressedData { frame });
+    }
+    let mut pcm = Vec::with_capacity(samples);
+    let mut position = 0;
+    while pcm.len() < samples {
+        let head = data
+            .get(position..position + 6)
+            .ok_or(DecodeError::Truncated { frame })?;
+        let count = ((head[0] & 0x1f) as usize) << 8 | head[1] as usize;
+        let coding = head[0] >> 5;
+        let checksum = BigEndian::read_u16(&head[4..6]);
+        let body = &data[position + 6..];
+        let (values, length) = match coding {
+            // stored as is
+            7 => {
+                let mut reader = BitReader {
+                    data: body,
+                    position: 0,
+                };
+                let values = (0..count)
+                    .map(|_| reader.read(bit_depth).map(|v| sign_extend(v, bit_depth)))
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or(DecodeError::Truncated { frame })?;
+                (values, (count * bit_depth as usize).div_ceil(8))
+            }
+            0 | 2 => {
+                let length = BigEndian::read_u16(&head[2..4]) as usize;
+                let body = body.get(..length).ok_or(DecodeError::Truncated { frame })?;
+                let values = expand_block(body, count, bit_depth, coding == 2)
+                    .ok_or(DecodeError::InvalidCompressedData { frame })?;
+                (values, length)
+            }
+            _ => return Err(DecodeError::InvalidCompressedData { frame }),
+        };
+
+        let shift = 16 - bit_depth;
+        let mut sum = 0u16;
+        for value in values {
+            let value = value << shift;
+            sum = sum.wrapping_add(value as u16);
+            pcm.push(value as i16);
+        }
+        if sum != checksum || count == 0 {
+            return Err(DecodeError::InvalidCompressedData { frame });
+        }
+        position += 6 + length;
+    }
+    pcm.truncate(samples);
+    Ok(pcm)
+}
+
+// Decodes a compressed block. Runs of samples are stored with one of four bit
+// widths, or the full bit depth. A run ends with the most negative value of its
+// width, followed by the width of the next run as a 2 bit index into the
+// widths other than the current one. With prediction, narrow runs store the
+// difference to a linear extrapolation of the two previous samples.
+fn expand_block(data: &[u8], count: usize, bit_depth: u32, predict: bool) -> Option<Vec<i32>> {
+    let mut reader = BitReader { data, position: 0 };
+    let mut widths = [0; 4];
+    for width in widths.iter_mut() {
+        *width = (reader.read(4)? + 1) & 0xf;
+    }
+    // the first run is always at the full bit depth
+    if reader.read(2)? != 3 {
+        return None;
+    }
+
+    let mut width = bit_depth;
+    let mut values = Vec::with_capacity(count);
+    let (mut previous, mut before) = (0, 0);
+    while values.len() < count {
+        let raw = reader.read(width)?;
+        if raw == 1 << (width - 1) {
+            // the most negative full width value is followed by a 0 bit, and
+            // the end of a run by a 1 bit
+            let end = width != bit_depth || reader.read(1)? == 1;
+            if end {
+                let index = reader.read(2)? as usize;
+                width = if width == bit_depth {
+                    widths[index]
+                } else if index == 3 {
+                    bit_depth
+                } else {
+                    let current = widths.iter().position(|&w| w == width)?;
+                    widths[if index >= current { index + 1 } else { index }]
+                };
+                if width == 0 {
+                    return None;
+                }
+                continue;
+            }
+        }
+
+        let mut value = sign_extend(raw, width);
+        if predict && width < bit_depth {
+            value += previous * 2 - before;
+        }
+        before = previous;
+        previous = value;
+        values.push(value);
+    }
+    Some(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsp::Rng;
+    use crate::pattern::Pattern;
+    use crate::{Calibration, Padding, SyroStream};
+    use std::time::Duration;
+
+    fn noise(len: usize, seed: u64) -> Vec<i16> {
+        let mut rng = Rng(seed);
+        (0..len).map(|_| rng.jitter(20000) as i16).collect()
+    }
+
+    fn sine(len: usize) -> Vec<i16> {
+        (0..len)
+            .map(|i| ((i as f32 * 0.05).sin() * 12000.0) as i16)
+            .collect()
+    }
+
+    #[test]
+    fn round_trip() -> anyhow::Result<()> {
+        let linear = noise(3000, 1);
+        let mut syro_stream = SyroStream::default();
+        syro_stream
+            .add_sample(3, linear.clone(), 31250, None)?
+            .add_sample(4, sine(5000), 44100, Some(16))?
+            .erase_sample(5)?
+            .add_pattern(2, Pattern::default())?;
+
+        let items = decode(&syro_stream.generate()?)?;
+        assert_eq!(items.len(), 4);
+        assert_eq!(
+            items[0],
+            DecodedItem::Sample {
+                index: 3,
+                data: linear,
+                sample_rate: 31250,
+                compression: None,
+            }
+        );
+        match &items[1] {
+            DecodedItem::Sample {
+                index,
+                data,
+                sample_rate,
+                compression,
+            } => {
+                assert_eq!((*index, *sample_rate, *compression), (4, 44100, Some(16)));
+                assert_eq!(data, &sine(5000));
+            }
+            item => panic!("unexpected item {:?}", item),
+        }
+        assert_eq!(items[2], DecodedItem::Erase { index: 5 });
+        assert_eq!(
+            items[3],
+            DecodedItem::Pattern {
+                index: 2,
+                data: Pattern::default().to_bytes(),
+            }
+        );
+        assert_eq!(items[3].slot(), Some(SlotId::Pattern(2)));
+        Ok(())
+    }
+
+    #[test]
+    fn compressed() -> anyhow::Result<()> {
+        // noise doesn't compress and is stored as is, the sine is predicted
+        for data in [noise(5000, 2), sine(5000)].iter() {
+            for &bit_depth in [8, 12].iter() {
+                let mut syro_stream = SyroStream::default();
+                syro_stream.add_sample(0, data.clone(), 31250, Some(bit_depth))?;
+                let items = decode(&syro_stream.generate()?)?;
+                let step = 1 << (16 - bit_depth);
+                let quantized: Vec<i16> = data.iter().map(|v| v / step * step).collect();
+                assert_eq!(
+                    items,
+                    vec![DecodedItem::Sample {
+                        index: 0,
+                        data: quantized,
+                        sample_rate: 31250,
+                        compression: Some(bit_depth),
+                    }]
+                );
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn all_data() -> anyhow::Result<()> {
+        let mut data: Vec<u8> = (0..ALL_INFO_SIZE).map(|i| i as u8).collect();
+        for value in sine(3000) {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        let items = decode(&SyroStream::reset(data.clone(), None)?)?;
+        assert_eq!(
+            items,
+            vec![DecodedItem::AllData {
+                data: data.clone(),
+                compression: None,
+            }]
+        );
+        assert_eq!(items[0].slot(), None);
+
+        let items = decode(&SyroStream::reset(data.clone(), Some(16))?)?;
+        assert_eq!(
+            items,
+            vec![DecodedItem::AllData {
+                data,
+                compression: Some(16),
+            }]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn padding_and_gain() -> anyhow::Result<()> {
+        let mut syro_stream = SyroStream::default();
+        syro_stream
+            .padding(Padding {
+                lead_in: Duration::from_millis(300),
+                lead_out: Duration::from_millis(100),
+                ramp: Duration::from_millis(5),
+            })
+            .calibration(Some(Calibration::default()))
+            .gain_db(-12.0)?
+            .erase_sample(7)?;
+        let items = decode(&syro_stream.generate()?)?;
+        assert_eq!(items, vec![DecodedItem::Erase { index: 7 }]);
+        Ok(())
+    }
+
+    #[test]
+    fn errors() -> anyhow::Result<()> {
+        assert_eq!(decode(&[]), Err(DecodeError::NoCarrier));
+        assert_eq!(decode(&vec![0; 10000]), Err(DecodeError::NoCarrier));
+
+        let mut syro_stream = SyroStream::default();
+        syro_stream.add_sample(0, noise(1000, 3), 31250, None)?;
+        let mut stream = syro_stream.generate()?;
+        // silence a stretch in the middle of the data block
+        let frames = stream.len() / 2;
+        let middle = frames - 3000 * CYCLE - 200 * CYCLE;
+        for value in stream[middle * 2..(middle + 40) * 2].iter_mut() {
+            *value = 0;
+        }
+        assert!(matches!(decode(&stream), Err(DecodeError::Crc { .. })));
+
+        // cut off after the header
+        let truncated = &stream[..(10000 + 49 + 100) * CYCLE * 2];
+        assert!(matches!(
+            decode(truncated),
+            Err(DecodeError::Truncated { frame: 80000 })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn checks() {
+        assert_eq!(crc16(b"123456789"), 0x29b1);
+        assert_eq!(sample_rate(speed(44100)), 44100);
+        assert_eq!(sample_rate(speed(8000)), 8000);
+        assert_eq!(sample_rate(speed(31250)), 31250);
+        assert_eq!(sample_rate(speed(40000)), 40000);
+        assert_eq!(sign_extend(0b100, 3), -4);
+        assert_eq!(sign_extend(0b011, 3), 3);
+    }
+}
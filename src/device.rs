@@ -0,0 +1,75 @@
+//! Capabilities of the devices receiving syro streams
+
+/// Kinds of data carried by a syro stream
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum DataType {
+    Sample,
+    CompressedSample,
+    SampleErase,
+    AllData,
+    CompressedAllData,
+    Pattern,
+}
+
+/// Limits and assumptions of a device receiving syro streams
+///
+/// KORG uses the SYRO scheme for more than one device, support for another
+/// device starts with an implementation of this trait.
+pub trait DeviceProfile {
+    /// Name of the device, for messages
+    const NAME: &'static str;
+    /// Number of sample slots
+    const SAMPLE_SLOTS: usize;
+    /// Number of pattern slots
+    const PATTERN_SLOTS: usize;
+    /// Total sample memory in bytes
+    const SAMPLE_MEMORY: usize;
+    /// Lowest sample rate played back at the right pitch
+    const MIN_SAMPLE_RATE: u32;
+    /// Highest sample rate played back at the right pitch
+    const MAX_SAMPLE_RATE: u32;
+    /// Sample rate the transfer audio is generated at
+    const OUTPUT_SAMPLE_RATE: u32;
+    /// Kinds of data the device accepts
+    const DATA_TYPES: &'static [DataType];
+
+    /// Returns true if the device accepts the kind of data
+    fn supports(data_type: DataType) -> bool {
+        Self::DATA_TYPES.contains(&data_type)
+    }
+}
+
+/// The KORG Volca Sample, the device targeted by [SyroStream](crate::SyroStream)
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct VolcaSample;
+
+impl DeviceProfile for VolcaSample {
+    const NAME: &'static str = "volca sample";
+    const SAMPLE_SLOTS: usize = 100;
+    const PATTERN_SLOTS: usize = 10;
+    const SAMPLE_MEMORY: usize = 4 * 1024 * 1024;
+    const MIN_SAMPLE_RATE: u32 = 8000;
+    const MAX_SAMPLE_RATE: u32 = 48000;
+    const OUTPUT_SAMPLE_RATE: u32 = 44100;
+    const DATA_TYPES: &'static [DataType] = &[
+        DataType::Sample,
+        DataType::CompressedSample,
+        DataType::SampleErase,
+        DataType::AllData,
+        DataType::CompressedAllData,
+        DataType::Pattern,
+    ];
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn volca_sample() {
+        assert_eq!(VolcaSample::SAMPLE_MEMORY, crate::MAX_SAMPLE_SIZE);
+        assert_eq!(VolcaSample::OUTPUT_SAMPLE_RATE, crate::OUTPUT_SAMPLE_RATE);
+        assert!(VolcaSample::supports(DataType::Pattern));
+        assert!(VolcaSample::supports(DataType::CompressedAllData));
+    }
+}
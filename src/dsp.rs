@@ -0,0 +1,424 @@
+//! Sample processing helpers
+
+// SplitMix64, small deterministic generator for randomization and dither
+pub(crate) struct Rng(pub(crate) u64);
+
+impl Rng {
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // uniform in [0, 1)
+    pub(crate) fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    // uniform in [-amount, amount]
+    pub(crate) fn jitter(&mut self, amount: u32) -> i32 {
+        let span = 2 * amount as u64 + 1;
+        (self.next_u64() % span) as i32 - amount as i32
+    }
+}
+
+/// Resamples PCM data with linear interpolation
+pub(crate) fn resample(data: &[i16], from: u32, to: u32) -> Vec<i16> {
+    if from == to || data.is_empty() {
+        return data.to_vec();
+    }
+    let len = (data.len() as u64 * to as u64).div_ceil(from as u64) as usize;
+    let step = from as f64 / to as f64;
+    (0..len)
+        .map(|i| {
+            let position = i as f64 * step;
+            let index = position as usize;
+            let fraction = position - index as f64;
+            let a = data[index.min(data.len() - 1)] as f64;
+            let b = data[(index + 1).min(data.len() - 1)] as f64;
+            (a + (b - a) * fraction).round() as i16
+        })
+        .collect()
+}
+
+// zero crossings of the resampling kernel on each side, at the input rate
+const SINC_ZERO_CROSSINGS: usize = 32;
+// kernel table entries per input frame
+const SINC_PHASES: usize = 256;
+
+/// Windowed sinc resampler for interleaved stereo PCM streams
+///
+/// Input can be fed in chunks of any size, the output is the same as when
+/// resampling the whole stream at once.
+pub(crate) struct Resampler {
+    // input frames per output frame
+    step: f64,
+    half_width: usize,
+    kernel: Vec<f32>,
+    // input frames still needed, starting with half_width frames of silence
+    buffer: Vec<[f32; 2]>,
+    // input frames dropped from the front of the buffer
+    dropped: usize,
+    produced: usize,
+    total_output: usize,
+}
+
+impl Resampler {
+    pub(crate) fn new(from: u32, to: u32, input_frames: usize) -> Self {
+        let step = from as f64 / to as f64;
+        // lowers the cutoff below the output nyquist frequency when downsampling
+        let cutoff = (to as f64 / from as f64).min(1.0) * 0.97;
+        let half_width = (SINC_ZERO_CROSSINGS as f64 / cutoff).ceil() as usize;
+        let kernel = (0..=half_width * SINC_PHASES)
+            .map(|i| {
+                let t = i as f64 / SINC_PHASES as f64;
+                let x = std::f64::consts::PI * cutoff * t;
+                let sinc = if i == 0 { 1.0 } else { x.sin() / x };
+                // blackman window
+                let u = std::f64::consts::PI * t / half_width as f64;
+                let window = 0.42 + 0.5 * u.cos() + 0.08 * (2.0 * u).cos();
+                (cutoff * sinc * window) as f32
+            })
+            .collect();
+        Self {
+            step,
+            half_width,
+            kernel,
+            buffer: vec![[0.0; 2]; half_width],
+            dropped: 0,
+            produced: 0,
+            total_output: (input_frames as f64 / step).round() as usize,
+        }
+    }
+
+    /// Number of output frames for the whole stream
+    pub(crate) fn total_output(&self) -> usize {
+        self.total_output
+    }
+
+    /// Feeds interleaved input, returning the interleaved output it completes
+    pub(crate) fn process(&mut self, input: &[i16]) -> Vec<i16> {
+        self.buffer
+            .extend(input.chunks_exact(2).map(|f| [f[0] as f32, f[1] as f32]));
+        self.drain(false)
+    }
+
+    /// Flushes the output still pending at the end of the input
+    pub(crate) fn finish(&mut self) -> Vec<i16> {
+        self.drain(true)
+    }
+
+    fn drain(&mut self, finished: bool) -> Vec<i16> {
+        let mut output = vec![];
+        while self.produced < self.total_output {
+            let position = self.produced as f64 * self.step;
+            let center = position as usize;
+            // index of the last input frame needed, relative to the buffer
+            let last = center + 2 * self.half_width - self.dropped;
+            if !finished && last >= self.buffer.len() {
+                break;
+            }
+            let frame = self.interpolate(position, center);
+            output.extend(
+                frame
+                    .iter()
+                    .map(|v| v.round().clamp(-32768.0, 32767.0) as i16),
+            );
+            self.produced += 1;
+        }
+
+        let needed = (self.produced as f64 * self.step) as usize;
+        let drop = needed.saturating_sub(self.dropped).min(self.buffer.len());
+        self.buffer.drain(..drop);
+        self.dropped += drop;
+        output
+    }
+
+    fn interpolate(&self, position: f64, center: usize) -> [f32; 2] {
+        let mut frame = [0.0; 2];
+        // input frame i sits at buffer index i + half_width - dropped
+        for index in center + 1..=center + 2 * self.half_width {
+            let input = match self.buffer.get(index - self.dropped) {
+                Some(input) => input,
+                None => break,
+            };
+            let distance = (position + self.half_width as f64 - index as f64).abs();
+            let weight = self.weight(distance);
+            frame[0] += input[0] * weight;
+            frame[1] += input[1] * weight;
+        }
+        frame
+    }
+
+    // kernel at a distance in input frames, linearly interpolated from the table
+    fn weight(&self, distance: f64) -> f32 {
+        let x = distance * SINC_PHASES as f64;
+        let i = x as usize;
+        if i + 1 >= self.kernel.len() {
+            return 0.0;
+        }
+        let fraction = (x - i as f64) as f32;
+        self.kernel[i] + (self.kernel[i + 1] - self.kernel[i]) * fraction
+    }
+}
+
+// sine of the frequency with an amplitude of 1
+pub(crate) fn sine(frequency: u32, sample_rate: u32, frames: usize) -> Vec<f32> {
+    let step = 2.0 * std::f64::consts::PI * frequency as f64 / sample_rate as f64;
+    (0..frames)
+        .map(|i| (step * i as f64).sin() as f32)
+        .collect()
+}
+
+/// Changes the length of the data by the factor while keeping its pitch
+///
+/// Uses WSOLA, overlapping windows of the input that are shifted to line up
+/// with the previous window. Data shorter than two windows is returned as is.
+#[cfg(feature = "stretch")]
+pub(crate) fn time_stretch(data: &[i16], sample_rate: u32, factor: f64) -> Vec<i16> {
+    // 30ms windows overlapping by half
+    let window = ((sample_rate as f64 * 0.03) as usize).max(64) & !1;
+    let hop = window / 2;
+    let tolerance = window / 4;
+    if data.len() < 2 * window || factor == 1.0 {
+        return data.to_vec();
+    }
+
+    let output_len = (data.len() as f64 * factor).round() as usize;
+    let mut output = vec![0.0; output_len + window];
+    let mut weights = vec![0.0; output_len + window];
+    let hann: Vec<f64> = (0..window)
+        .map(|i| 0.5 - 0.5 * (2.0 * std::f64::consts::PI * i as f64 / window as f64).cos())
+        .collect();
+    let last_start = data.len() - window;
+
+    let mut previous = 0;
+    for segment in 0.. {
+        let output_start = segment * hop;
+        if output_start >= output_len {
+            break;
+        }
+        let nominal = ((output_start as f64 / factor) as usize).min(last_start);
+        let start = if segment == 0 {
+            0
+        } else {
+            // the input that would naturally follow the previous window
+            let natural = (previous + hop).min(last_start);
+            let lo = nominal.saturating_sub(tolerance);
+            let hi = (nominal + tolerance).min(last_start);
+            (lo..=hi)
+                .max_by_key(|&candidate| correlation(&data[natural..], &data[candidate..], hop))
+                .unwrap_or(nominal)
+        };
+        for (i, &w) in hann.iter().enumerate() {
+            output[output_start + i] += data[start + i] as f64 * w;
+            weights[output_start + i] += w;
+        }
+        previous = start;
+    }
+
+    output
+        .iter()
+        .zip(weights.iter())
+        .take(output_len)
+        .map(|(&value, &weight)| {
+            let value = if weight > 1e-3 { value / weight } else { 0.0 };
+            value.round().clamp(i16::MIN as f64, i16::MAX as f64) as i16
+        })
+        .collect()
+}
+
+// cross-correlation of the first `len` values, every fourth value is enough
+// to line up the windows
+#[cfg(feature = "stretch")]
+fn correlation(a: &[i16], b: &[i16], len: usize) -> i64 {
+    a.iter()
+        .zip(b.iter())
+        .take(len)
+        .step_by(4)
+        .map(|(&a, &b)| a as i64 * b as i64)
+        .sum()
+}
+
+/// Second order Butterworth high-pass filter
+pub(crate) fn high_pass(data: &mut [i16], sample_rate: u32, cutoff: u32) {
+    if sample_rate == 0 || cutoff as f64 >= sample_rate as f64 / 2.0 {
+        return;
+    }
+    // biquad coefficients from the RBJ audio EQ cookbook, Q = 1/sqrt(2)
+    let w0 = 2.0 * std::f64::consts::PI * cutoff as f64 / sample_rate as f64;
+    let alpha = w0.sin() / std::f64::consts::SQRT_2;
+    let cos = w0.cos();
+    let a0 = 1.0 + alpha;
+    let b0 = (1.0 + cos) / 2.0 / a0;
+    let b1 = -(1.0 + cos) / a0;
+    let a1 = -2.0 * cos / a0;
+    let a2 = (1.0 - alpha) / a0;
+
+    let (mut x1, mut x2, mut y1, mut y2) = (0.0, 0.0, 0.0, 0.0);
+    for value in data.iter_mut() {
+        let x = *value as f64;
+        let y = b0 * x + b1 * x1 + b0 * x2 - a1 * y1 - a2 * y2;
+        x2 = x1;
+        x1 = x;
+        y2 = y1;
+        y1 = y;
+        *value = y.round().clamp(i16::MIN as f64, i16::MAX as f64) as i16;
+    }
+}
+
+/// Scales the data so the loudest sample reaches full scale
+pub(crate) fn normalize(data: &mut [i16]) {
+    let peak = data.iter().map(|&v| (v as i32).abs()).max().unwrap_or(0);
+    if peak == 0 {
+        return;
+    }
+    let gain = i16::MAX as f64 / peak as f64;
+    for value in data.iter_mut() {
+        *value = (*value as f64 * gain)
+            .round()
+            .clamp(i16::MIN as f64, i16::MAX as f64) as i16;
+    }
+}
+
+/// Adds triangular dither of one step at the given bit depth, ahead of
+/// reducing the data to that depth
+pub(crate) fn dither(data: &mut [i16], bit_depth: u32) {
+    if bit_depth >= 16 {
+        return;
+    }
+    let step = 1u32 << (16 - bit_depth);
+    let mut rng = Rng(data.len() as u64);
+    for value in data.iter_mut() {
+        let noise = rng.jitter(step / 2) + rng.jitter(step / 2);
+        *value = (*value as i32 + noise).clamp(i16::MIN as i32, i16::MAX as i32) as i16;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resampler() {
+        // 1 second of a 1kHz sine wave
+        let input: Vec<i16> = (0..44100)
+            .flat_map(|i| {
+                let t = i as f64 / 44100.0;
+                let v = (10000.0 * (2.0 * std::f64::consts::PI * 1000.0 * t).sin()) as i16;
+                vec![v, v]
+            })
+            .collect();
+
+        let mut resampler = Resampler::new(44100, 48000, 44100);
+        assert_eq!(resampler.total_output(), 48000);
+        let mut output = resampler.process(&input);
+        output.extend(resampler.finish());
+        assert_eq!(output.len(), 48000 * 2);
+
+        // away from the edges the output matches the sine wave at 48kHz
+        for i in 1000..47000 {
+            let t = i as f64 / 48000.0;
+            let expected = 10000.0 * (2.0 * std::f64::consts::PI * 1000.0 * t).sin();
+            assert!(
+                (output[i * 2] as f64 - expected).abs() < 20.0,
+                "frame {}",
+                i
+            );
+        }
+
+        let mut chunked = Resampler::new(44100, 48000, 44100);
+        let mut chunked_output: Vec<i16> = input
+            .chunks(1000)
+            .flat_map(|chunk| chunked.process(chunk))
+            .collect();
+        chunked_output.extend(chunked.finish());
+        assert_eq!(chunked_output, output);
+    }
+
+    #[cfg(feature = "stretch")]
+    #[test]
+    fn test_time_stretch() {
+        // 1 second of a 200Hz sine wave
+        let sine: Vec<i16> = (0..8000)
+            .map(|i| {
+                (10000.0 * (2.0 * std::f64::consts::PI * 200.0 * i as f64 / 8000.0).sin()) as i16
+            })
+            .collect();
+        let crossings = |data: &[i16]| data.windows(2).filter(|w| w[0] < 0 && w[1] >= 0).count();
+
+        let stretched = time_stretch(&sine, 8000, 1.5);
+        assert_eq!(stretched.len(), 12000);
+        // the pitch is kept, so 1.5 seconds hold 300 cycles
+        assert!((295..=305).contains(&crossings(&stretched)));
+
+        let shortened = time_stretch(&sine, 8000, 0.5);
+        assert_eq!(shortened.len(), 4000);
+        assert!((97..=103).contains(&crossings(&shortened)));
+
+        assert_eq!(time_stretch(&sine[..100], 8000, 2.0), sine[..100].to_vec());
+    }
+
+    #[test]
+    fn test_high_pass() {
+        let sine = |frequency: f64| -> Vec<i16> {
+            (0..8000)
+                .map(|i| {
+                    (10000.0 * (2.0 * std::f64::consts::PI * frequency * i as f64 / 8000.0).sin())
+                        as i16
+                })
+                .collect()
+        };
+        let peak = |data: &[i16]| data[4000..].iter().map(|v| v.unsigned_abs()).max().unwrap();
+
+        // a DC offset decays away
+        let mut dc = vec![5000; 8000];
+        high_pass(&mut dc, 8000, 50);
+        assert!(peak(&dc) < 10);
+
+        let mut low = sine(10.0);
+        high_pass(&mut low, 8000, 100);
+        assert!(peak(&low) < 200);
+
+        let mut high = sine(1000.0);
+        high_pass(&mut high, 8000, 100);
+        assert!(peak(&high) > 9800);
+    }
+
+    #[test]
+    fn test_resample() {
+        let data = vec![0, 100, 200, 300];
+        assert_eq!(resample(&data, 44100, 44100), data);
+        assert_eq!(
+            resample(&data, 2, 4),
+            vec![0, 50, 100, 150, 200, 250, 300, 300]
+        );
+        assert_eq!(resample(&data, 4, 2), vec![0, 200]);
+        assert!(resample(&[], 96000, 48000).is_empty());
+    }
+
+    #[test]
+    fn test_normalize() {
+        let mut data = vec![0, 1000, -2000];
+        normalize(&mut data);
+        assert_eq!(data, vec![0, 16384, -32767]);
+
+        let mut silence = vec![0; 4];
+        normalize(&mut silence);
+        assert_eq!(silence, vec![0; 4]);
+    }
+
+    #[test]
+    fn test_dither() {
+        let mut data = vec![1000; 64];
+        dither(&mut data, 8);
+        assert!(data.iter().all(|&v| (1000 - 256..=1000 + 256).contains(&v)));
+        assert!(data.iter().any(|&v| v != 1000));
+
+        let mut data = vec![1000; 64];
+        dither(&mut data, 16);
+        assert_eq!(data, vec![1000; 64]);
+    }
+}
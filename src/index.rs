@@ -0,0 +1,116 @@
+//! Validated slot indices
+use std::convert::TryFrom;
+
+use crate::pattern::PATTERN_INDEX_ERROR_NAME;
+use crate::{DeviceProfile, SlotId, SyroError, VolcaSample, SAMPLE_INDEX_ERROR_NAME};
+
+macro_rules! index_type {
+    ($(#[$m:meta])* $name:ident, $slots:expr, $error_name:expr, $slot:ident) => {
+        $(#[$m])*
+        #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name(u8);
+
+        impl $name {
+            /// Returns the index as an integer
+            pub fn get(self) -> u8 {
+                self.0
+            }
+
+            fn check(index: u64) -> Result<Self, SyroError> {
+                if index >= $slots as u64 {
+                    return Err(SyroError::OutOfBounds {
+                        val: u32::try_from(index).unwrap_or(u32::MAX),
+                        name: $error_name,
+                        lo: 0,
+                        hi: $slots - 1,
+                    });
+                }
+                Ok(Self(index as u8))
+            }
+        }
+
+        index_type!(@try_from $name, u8, u16, u32, u64, usize);
+        index_type!(@into $name, u8, u16, u32, u64, usize);
+
+        impl From<$name> for SlotId {
+            fn from(index: $name) -> Self {
+                SlotId::$slot(index.0)
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{}", self.0)
+            }
+        }
+    };
+    (@try_from $name:ident, $($int:ty),*) => {
+        $(
+            impl TryFrom<$int> for $name {
+                type Error = SyroError;
+
+                fn try_from(index: $int) -> Result<Self, Self::Error> {
+                    Self::check(index as u64)
+                }
+            }
+        )*
+    };
+    (@into $name:ident, $($int:ty),*) => {
+        $(
+            impl From<$name> for $int {
+                fn from(index: $name) -> Self {
+                    index.0 as $int
+                }
+            }
+        )*
+    };
+}
+
+index_type!(
+    /// Index of a sample slot, validated to be in the range 0-99
+    SampleIndex,
+    VolcaSample::SAMPLE_SLOTS,
+    SAMPLE_INDEX_ERROR_NAME,
+    Sample
+);
+
+index_type!(
+    /// Index of a pattern slot, validated to be in the range 0-9
+    PatternIndex,
+    VolcaSample::PATTERN_SLOTS,
+    PATTERN_INDEX_ERROR_NAME,
+    Pattern
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SyroStream;
+
+    #[test]
+    fn test_index() -> anyhow::Result<()> {
+        assert_eq!(SampleIndex::try_from(99u32)?.get(), 99);
+        assert_eq!(u32::from(SampleIndex::try_from(42u8)?), 42);
+        assert_eq!(
+            SampleIndex::try_from(300usize),
+            Err(SyroError::OutOfBounds {
+                val: 300,
+                name: SAMPLE_INDEX_ERROR_NAME,
+                lo: 0,
+                hi: 99
+            })
+        );
+        assert_eq!(
+            SlotId::from(PatternIndex::try_from(9u8)?),
+            SlotId::Pattern(9)
+        );
+        assert!(PatternIndex::try_from(10u8).is_err());
+
+        // indices above 255 used to wrap around when checked as u8
+        assert!(SyroStream::default().erase_sample(300).is_err());
+        assert!(SyroStream::default()
+            .add_pattern(265, Default::default())
+            .is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,121 @@
+//! Background generation on a std thread
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+use crate::{SyroError, SyroStream};
+
+// frames rendered between progress updates and cancellation checks
+const CHUNK_FRAMES: usize = 4096;
+
+#[derive(Default)]
+struct Progress {
+    total_frames: AtomicUsize,
+    done_frames: AtomicUsize,
+    cancelled: AtomicBool,
+}
+
+/// Handle to a syro stream being generated on a background thread
+///
+/// Created with [spawn_generate](SyroStream::spawn_generate). The stream is
+/// rendered by a [SyroGenerator](crate::SyroGenerator) on the background thread.
+pub struct GenerationJob {
+    progress: Arc<Progress>,
+    thread: thread::JoinHandle<Result<Vec<i16>, SyroError>>,
+}
+
+impl GenerationJob {
+    /// Returns the generated fraction of the stream, from 0.0 to 1.0
+    pub fn progress(&self) -> f32 {
+        let total = self.progress.total_frames.load(Ordering::Relaxed);
+        if total == 0 {
+            return 0.0;
+        }
+        self.progress.done_frames.load(Ordering::Relaxed) as f32 / total as f32
+    }
+
+    /// Returns true once the background thread has stopped
+    pub fn is_finished(&self) -> bool {
+        self.thread.is_finished()
+    }
+
+    /// Asks the generation to stop, [join](GenerationJob::join) then returns
+    /// [SyroError::Cancelled]
+    pub fn cancel(&self) {
+        self.progress.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Waits for the generation to finish and returns the PCM data
+    pub fn join(self) -> Result<Vec<i16>, SyroError> {
+        match self.thread.join() {
+            Ok(result) => result,
+            Err(panic) => std::panic::resume_unwind(panic),
+        }
+    }
+}
+
+impl SyroStream {
+    /// Generates the syro stream on a background thread
+    ///
+    /// Same as [generate](SyroStream::generate), without requiring an async
+    /// runtime. Errors are returned by [join](GenerationJob::join).
+    pub fn spawn_generate(self) -> GenerationJob {
+        let progress = Arc::new(Progress::default());
+        let job_progress = progress.clone();
+        let thread = thread::spawn(move || generate(self, &job_progress));
+        GenerationJob { progress, thread }
+    }
+}
+
+fn generate(stream: SyroStream, progress: &Progress) -> Result<Vec<i16>, SyroError> {
+    let mut generator = stream.generator()?;
+    let total_frames = generator.remaining_frames();
+    progress.total_frames.store(total_frames, Ordering::Relaxed);
+
+    let mut buffer = vec![0; total_frames * 2];
+    for chunk in buffer.chunks_mut(CHUNK_FRAMES * 2) {
+        if progress.cancelled.load(Ordering::Relaxed) {
+            return Err(SyroError::Cancelled);
+        }
+        let written = generator.fill(chunk)?;
+        progress
+            .done_frames
+            .fetch_add(written / 2, Ordering::Relaxed);
+    }
+    generator.end()?;
+    Ok(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{SyroError, SyroStream};
+
+    fn stream() -> anyhow::Result<SyroStream> {
+        let mut syro_stream = SyroStream::default();
+        syro_stream.add_sample(0, vec![0; 44100], 44100, None)?;
+        Ok(syro_stream)
+    }
+
+    #[test]
+    fn spawn_generate() -> anyhow::Result<()> {
+        let job = stream()?.spawn_generate();
+        assert!(job.progress() <= 1.0);
+        assert_eq!(job.join()?, stream()?.generate()?);
+        Ok(())
+    }
+
+    #[test]
+    fn cancel() -> anyhow::Result<()> {
+        let job = stream()?.spawn_generate();
+        job.cancel();
+        match job.join() {
+            // generation may finish before the cancellation is seen
+            Ok(data) => assert_eq!(data, stream()?.generate()?),
+            Err(err) => assert_eq!(err, SyroError::Cancelled),
+        }
+
+        let job = SyroStream::default().spawn_generate();
+        assert_eq!(job.join(), Err(SyroError::EmptyStream));
+        Ok(())
+    }
+}
@@ -50,18 +50,55 @@
 //! wav::write(header, &wav::BitDepth::Sixteen(data), &mut BufWriter::new(output));
 //! # Ok::<(), korg_syro::SyroError>(())
 //! ```
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::fmt;
+use std::fs::File;
+use std::io::Write;
 use std::mem::MaybeUninit;
+use std::path::Path;
+use std::time::Duration;
 
-use array_init;
 use byteorder::{ByteOrder, LittleEndian};
 use korg_syro_sys as syro;
 use thiserror::Error;
 
+pub use bank::{BankManager, BankSwitch, Kit};
+pub use context::GenerationContext;
+pub use device::{DataType, DeviceProfile, VolcaSample};
+pub use index::{PatternIndex, SampleIndex};
+pub use job::GenerationJob;
+pub use planner::{FitStrategy, SlotPlanner};
+pub use state::{DeviceState, Projection};
+pub use verify::{Mismatch, VerificationReport};
+
 #[macro_use]
 mod macros;
 use macros::*;
 
+pub mod analysis;
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impl;
+#[cfg(feature = "async")]
+mod async_impl;
+mod bank;
+mod context;
+pub mod decode;
+mod device;
+mod dsp;
+mod index;
+mod job;
+#[cfg(feature = "parallel")]
+mod parallel;
 pub mod pattern;
+mod planner;
+#[cfg(feature = "presets")]
+pub mod presets;
+pub mod preview;
+#[cfg(feature = "project")]
+pub mod project;
+mod state;
+mod verify;
 
 #[derive(Error, Debug, PartialEq)]
 pub enum SyroError {
@@ -76,10 +113,164 @@ pub enum SyroError {
     #[error("empty stream, provide at least one sample or pattern")]
     EmptyStream,
 
+    #[error("invalid pattern data size {size}, expected {}", pattern::PATTERN_SIZE)]
+    InvalidPatternSize { size: usize },
+
+    #[error("invalid pattern {field} {found:#x}, expected {expected:#x}")]
+    InvalidPatternHeader {
+        field: &'static str,
+        found: u32,
+        expected: u32,
+    },
+
+    #[error("invalid step '{input}', expected a step number from 1 to 16")]
+    InvalidStep { input: String },
+
+    #[error("invalid text grid at line {line}, column {column}: {reason}")]
+    InvalidTextGrid {
+        line: usize,
+        column: usize,
+        reason: String,
+    },
+
+    #[error("unsupported sample rate {rate}, see SampleRate for the supported rates")]
+    UnsupportedSampleRate { rate: u32 },
+
+    #[error("invalid MIDI file: {reason}")]
+    InvalidMidi { reason: String },
+
+    #[error(
+        "sample {index} is {size} bytes, the device holds at most {} bytes",
+        MAX_SAMPLE_SIZE
+    )]
+    SampleTooLarge { index: u32, size: usize },
+
+    #[error("data of {size} bytes is too large for a syro stream")]
+    DataTooLarge { size: usize },
+
+    #[error("stream of {samples} samples is too long for a WAV file")]
+    WavTooLarge { samples: usize },
+
+    #[error(
+        "sample of {size} bytes does not fit in the remaining {available} bytes of sample memory"
+    )]
+    InsufficientMemory { size: usize, available: usize },
+
+    #[error("stream of {duration:?} does not fit in the transfer time of {limit:?}")]
+    TransferTooLong { duration: Duration, limit: Duration },
+
+    #[error("no free sample slot left")]
+    NoFreeSlot,
+
+    #[error("no kit named '{name}'")]
+    UnknownKit { name: String },
+
+    #[error("nothing is queued for {slot}")]
+    SlotEmpty { slot: SlotId },
+
+    #[error("{slot} is already queued")]
+    SlotOccupied { slot: SlotId },
+
+    #[error("{} invalid entries: {}", .errors.len(), display_entry_errors(.errors))]
+    InvalidEntries { errors: Vec<(usize, SyroError)> },
+
+    #[error("invalid pattern {index} in bank: {source}")]
+    InvalidPatternInBank {
+        index: usize,
+        #[source]
+        source: Box<SyroError>,
+    },
+
+    #[error("{slot} ({data_type:?}) was rejected: {source}")]
+    SlotRejected {
+        slot: SlotId,
+        data_type: DataType,
+        #[source]
+        source: Box<SyroError>,
+    },
+
+    #[error("the generation task was cancelled")]
+    Cancelled,
+
+    #[error("the generated stream could not be decoded: {source}")]
+    Undecodable {
+        #[source]
+        source: decode::DecodeError,
+    },
+
+    #[error("gain of {gain_db} dB is outside of {} to 0 dB", MIN_GAIN_DB)]
+    GainOutOfRange { gain_db: f32 },
+
+    #[error("time-stretch factor {factor} is outside of {} to {}", STRETCH_RANGE.0, STRETCH_RANGE.1)]
+    StretchOutOfRange { factor: f32 },
+
+    #[error("failed to write the syro stream: {reason}")]
+    Io {
+        kind: std::io::ErrorKind,
+        reason: String,
+    },
+
     #[error("unhandled SyroStatus {status:?}")]
     SyroStatus { status: syro::SyroStatus },
 }
 
+impl From<std::io::Error> for SyroError {
+    fn from(err: std::io::Error) -> Self {
+        SyroError::Io {
+            kind: err.kind(),
+            reason: err.to_string(),
+        }
+    }
+}
+
+fn display_entry_errors(errors: &[(usize, SyroError)]) -> String {
+    let entries: Vec<_> = errors
+        .iter()
+        .map(|(entry, err)| format!("entry {}: {}", entry, err))
+        .collect();
+    entries.join("; ")
+}
+
+/// Identifies a sample or pattern slot on the device
+///
+/// Ordered the way slots are transferred, samples before patterns.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SlotId {
+    /// Sample slot 0-99
+    Sample(u8),
+    /// Pattern slot 0-9
+    Pattern(u8),
+}
+
+impl std::fmt::Display for SlotId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SlotId::Sample(index) => write!(f, "sample {}", index),
+            SlotId::Pattern(index) => write!(f, "pattern {}", index),
+        }
+    }
+}
+
+/// Problems found by [validate](SyroStream::validate)
+#[derive(Error, Debug, PartialEq)]
+pub enum ValidationIssue {
+    #[error("part {part} of pattern {pattern} plays sample {sample}, which is erased by the same stream")]
+    ErasedSample {
+        pattern: usize,
+        part: usize,
+        sample: u16,
+    },
+
+    #[error(
+        "part {part} of pattern {pattern} plays sample {sample}, which is not part of the stream"
+    )]
+    MissingSample {
+        pattern: usize,
+        part: usize,
+        sample: u16,
+    },
+}
+
 fn check_syro_status(status: syro::SyroStatus) -> Result<(), SyroError> {
     match status {
         syro::SyroStatus::Status_Success => Ok(()),
@@ -92,13 +283,212 @@ fn check_syro_status(status: syro::SyroStatus) -> Result<(), SyroError> {
         // SyroStatus::Status_NotEnoughMemory
         // SyroStatus::Status_InvalidHandle
         // SyroStatus::Status_NoData
-        _ => Err(SyroError::SyroStatus { status }),
+        _ => {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(?status, "syro call failed");
+            Err(SyroError::SyroStatus { status })
+        }
+    }
+}
+
+/// Size of the sample memory of the Volca Sample in bytes
+pub const MAX_SAMPLE_SIZE: usize = VolcaSample::SAMPLE_MEMORY;
+
+/// Lowest sample rate accepted by [RatePolicy::Strict]
+pub const MIN_SAMPLE_RATE: u32 = VolcaSample::MIN_SAMPLE_RATE;
+
+/// Highest sample rate accepted by [RatePolicy::Strict]
+pub const MAX_SAMPLE_RATE: u32 = VolcaSample::MAX_SAMPLE_RATE;
+
+/// Sample rate of the generated syro streams
+pub const OUTPUT_SAMPLE_RATE: u32 = VolcaSample::OUTPUT_SAMPLE_RATE;
+
+const SAMPLE_RATE_ERROR_NAME: &str = "sample_rate";
+
+/// Sample rates known to work well with the device
+///
+/// The Volca Sample plays back at 31.25 kHz, samples at other rates are
+/// converted to it on the device. 31.25 kHz keeps a sample as is and uses the
+/// least memory for its quality, higher rates only take more memory and
+/// transfer time. Lower rates save memory at the cost of high frequencies.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SampleRate {
+    Hz8000,
+    Hz11025,
+    Hz16000,
+    Hz22050,
+    /// The native rate of the device
+    Hz31250,
+    Hz32000,
+    Hz44100,
+    Hz48000,
+}
+
+impl SampleRate {
+    /// All supported rates, from lowest to highest
+    pub const ALL: [SampleRate; 8] = [
+        SampleRate::Hz8000,
+        SampleRate::Hz11025,
+        SampleRate::Hz16000,
+        SampleRate::Hz22050,
+        SampleRate::Hz31250,
+        SampleRate::Hz32000,
+        SampleRate::Hz44100,
+        SampleRate::Hz48000,
+    ];
+
+    pub fn hz(self) -> u32 {
+        match self {
+            SampleRate::Hz8000 => 8000,
+            SampleRate::Hz11025 => 11025,
+            SampleRate::Hz16000 => 16000,
+            SampleRate::Hz22050 => 22050,
+            SampleRate::Hz31250 => 31250,
+            SampleRate::Hz32000 => 32000,
+            SampleRate::Hz44100 => 44100,
+            SampleRate::Hz48000 => 48000,
+        }
     }
+
+    /// Returns the supported rate closest to `hz`, the higher one on a tie
+    pub fn closest(hz: u32) -> Self {
+        let distance = |rate: &SampleRate| (rate.hz() as i64 - hz as i64).abs();
+        let mut closest = SampleRate::Hz8000;
+        for rate in SampleRate::ALL.iter() {
+            if distance(rate) <= distance(&closest) {
+                closest = *rate;
+            }
+        }
+        closest
+    }
+}
+
+impl TryFrom<u32> for SampleRate {
+    type Error = SyroError;
+
+    fn try_from(hz: u32) -> Result<Self, Self::Error> {
+        SampleRate::ALL
+            .iter()
+            .copied()
+            .find(|rate| rate.hz() == hz)
+            .ok_or(SyroError::UnsupportedSampleRate { rate: hz })
+    }
+}
+
+impl From<SampleRate> for u32 {
+    fn from(rate: SampleRate) -> Self {
+        rate.hz()
+    }
+}
+
+impl fmt::Display for SampleRate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} Hz", self.hz())
+    }
+}
+
+/// Defines how [add_sample](SyroStream::add_sample) handles sample rates
+/// outside of [MIN_SAMPLE_RATE] to [MAX_SAMPLE_RATE]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum RatePolicy {
+    /// Rejects the sample
+    Strict,
+    /// Resamples to the closest supported rate
+    Resample,
+    /// Resamples every rate that is not a [SampleRate] to the closest one
+    Standard,
+    /// Passes the sample rate to the SYRO library unchecked
+    #[default]
+    Passthrough,
 }
 
+impl RatePolicy {
+    fn apply(self, data: Vec<i16>, sample_rate: u32) -> Result<(Vec<i16>, u32), SyroError> {
+        let supported = (MIN_SAMPLE_RATE..=MAX_SAMPLE_RATE).contains(&sample_rate);
+        match self {
+            RatePolicy::Strict if !supported => Err(SyroError::OutOfBounds {
+                val: sample_rate,
+                name: SAMPLE_RATE_ERROR_NAME,
+                lo: MIN_SAMPLE_RATE as usize,
+                hi: MAX_SAMPLE_RATE as usize,
+            }),
+            RatePolicy::Resample if !supported && sample_rate > 0 => {
+                let target = sample_rate.clamp(MIN_SAMPLE_RATE, MAX_SAMPLE_RATE);
+                Ok((dsp::resample(&data, sample_rate, target), target))
+            }
+            RatePolicy::Standard if sample_rate > 0 => {
+                let target = SampleRate::closest(sample_rate).hz();
+                Ok((dsp::resample(&data, sample_rate, target), target))
+            }
+            _ => Ok((data, sample_rate)),
+        }
+    }
+}
+
+/// Sample rate of the generated audio
+///
+/// The SYRO library generates streams at [OUTPUT_SAMPLE_RATE], other rates are
+/// resampled inside the crate. Use this when the playback device forces a
+/// rate, as resampling by the operating system can break the transfer.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum OutputRate {
+    #[default]
+    Hz44100,
+    Hz48000,
+    /// Any rate from 8kHz to 192kHz
+    Custom(u32),
+}
+
+impl OutputRate {
+    /// Returns the rate in Hz
+    pub fn hz(self) -> u32 {
+        match self {
+            OutputRate::Hz44100 => 44100,
+            OutputRate::Hz48000 => 48000,
+            OutputRate::Custom(rate) => rate,
+        }
+    }
+}
+
+/// Lowest gain accepted by [gain_db](SyroStream::gain_db)
+pub const MIN_GAIN_DB: f32 = -12.0;
+
+const STRETCH_RANGE: (f32, f32) = (0.5, 2.0);
+
+const HIGH_PASS_ERROR_NAME: &str = "high_pass";
+const HIGH_PASS_RANGE: (u32, u32) = (20, 500);
+
+const OUTPUT_RATE_ERROR_NAME: &str = "output_rate";
+const OUTPUT_RATE_RANGE: (u32, u32) = (8000, 192000);
+
 max_check!(sample_index, 99);
 bounds_check!(bit_depth, 8, 16);
 
+fn data_size(data: &[u8]) -> Result<u32, SyroError> {
+    u32::try_from(data.len()).map_err(|_| SyroError::DataTooLarge { size: data.len() })
+}
+
+/// A sample lowered in rate by [fit_to_budget](SyroStream::fit_to_budget)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Downsampled {
+    pub index: u32,
+    pub from_rate: u32,
+    pub to_rate: u32,
+    /// Size in bytes before downsampling
+    pub from_size: usize,
+    /// Size in bytes after downsampling
+    pub to_size: usize,
+}
+
+/// A sample compressed by [fit_transfer_time](SyroStream::fit_transfer_time)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Recompressed {
+    pub index: u32,
+    /// None when the sample was uncompressed
+    pub from_bit_depth: Option<u32>,
+    pub to_bit_depth: u32,
+}
+
 // Encapsulates ownership of SyroData
 struct SyroDataBundle {
     #[allow(dead_code)]
@@ -106,6 +496,22 @@ struct SyroDataBundle {
     syro_data: syro::SyroData,
 }
 
+// SAFETY: the raw pointer in syro_data points into the heap buffer owned by
+// data, which moves along with the bundle
+unsafe impl Send for SyroDataBundle {}
+
+impl Clone for SyroDataBundle {
+    fn clone(&self) -> Self {
+        let mut data = self.data.clone();
+        let mut syro_data = self.syro_data;
+        // erases carry no data and keep their null pointer
+        if !syro_data.pData.is_null() {
+            syro_data.pData = data.as_mut_ptr();
+        }
+        Self { data, syro_data }
+    }
+}
+
 impl SyroDataBundle {
     fn sample(
         index: u32,
@@ -113,21 +519,21 @@ impl SyroDataBundle {
         mut data: Vec<u8>,
         sample_rate: u32,
         bit_depth: u32,
-    ) -> Self {
+    ) -> Result<Self, SyroError> {
         let syro_data = syro::SyroData {
             DataType: data_type,
             pData: data.as_mut_ptr(),
             // the sample (0-99) or sequence pattern (0-9) number
             Number: index,
             // size of data to be converted (in bytes)
-            Size: data.len() as u32,
+            Size: data_size(&data)?,
             // The conversion bit depth. It can be set to 8-16. Seems unused when DataType = Sample_liner
             Quality: bit_depth,
             Fs: sample_rate,
             SampleEndian: korg_syro_sys::Endian::LittleEndian,
         };
 
-        Self { data, syro_data }
+        Ok(Self { data, syro_data })
     }
 
     fn erase(index: u32) -> Self {
@@ -147,32 +553,32 @@ impl SyroDataBundle {
         }
     }
 
-    fn reset(mut data: Vec<u8>) -> Self {
+    fn reset(mut data: Vec<u8>) -> Result<Self, SyroError> {
         let syro_data = syro::SyroData {
             DataType: syro::SyroDataType::DataType_Sample_All,
             pData: data.as_mut_ptr(),
-            Size: data.len() as u32,
+            Size: data_size(&data)?,
             Number: 0,
             Quality: 0,
             Fs: 44100,
             SampleEndian: korg_syro_sys::Endian::LittleEndian,
         };
 
-        Self { data, syro_data }
+        Ok(Self { data, syro_data })
     }
 
-    fn reset_compressed(mut data: Vec<u8>, bit_depth: u32) -> Self {
+    fn reset_compressed(mut data: Vec<u8>, bit_depth: u32) -> Result<Self, SyroError> {
         let syro_data = syro::SyroData {
             DataType: syro::SyroDataType::DataType_Sample_AllCompress,
             pData: data.as_mut_ptr(),
-            Size: data.len() as u32,
+            Size: data_size(&data)?,
             Number: 0,
             Quality: bit_depth,
             Fs: 44100,
             SampleEndian: korg_syro_sys::Endian::LittleEndian,
         };
 
-        Self { data, syro_data }
+        Ok(Self { data, syro_data })
     }
 
     fn pattern(index: u32, mut data: Vec<u8>) -> Self {
@@ -194,21 +600,256 @@ impl SyroDataBundle {
     }
 }
 
+/// Per-sample settings for [add_sample_with](SyroStream::add_sample_with)
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct AddSampleOptions {
+    sample_rate: u32,
+    compression: Option<u32>,
+    dither: bool,
+    normalize: bool,
+    high_pass: Option<u32>,
+    #[cfg(feature = "stretch")]
+    stretch: Option<Stretch>,
+    rate_policy: Option<RatePolicy>,
+}
+
+// target length of a time-stretched sample
+#[cfg(feature = "stretch")]
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Stretch {
+    Factor(f32),
+    Duration(Duration),
+}
+
+#[cfg(feature = "stretch")]
+impl Stretch {
+    fn factor(self, frames: usize, sample_rate: u32) -> Result<f32, SyroError> {
+        let factor = match self {
+            Stretch::Factor(factor) => factor,
+            Stretch::Duration(duration) => {
+                (duration.as_secs_f64() * sample_rate as f64 / frames.max(1) as f64) as f32
+            }
+        };
+        if !(STRETCH_RANGE.0..=STRETCH_RANGE.1).contains(&factor) {
+            return Err(SyroError::StretchOutOfRange { factor });
+        }
+        Ok(factor)
+    }
+}
+
+impl Default for AddSampleOptions {
+    fn default() -> Self {
+        Self::new(44100)
+    }
+}
+
+impl AddSampleOptions {
+    pub fn new(sample_rate: u32) -> Self {
+        Self {
+            sample_rate,
+            compression: None,
+            dither: false,
+            normalize: false,
+            high_pass: None,
+            #[cfg(feature = "stretch")]
+            stretch: None,
+            rate_policy: None,
+        }
+    }
+
+    /// Compresses the sample to the given bit depth, in the range of 8-16 bits
+    pub fn compression(&mut self, bit_depth: u32) -> Result<&mut Self, SyroError> {
+        check_bit_depth(bit_depth.min(u8::MAX as u32) as u8)?;
+        self.compression = Some(bit_depth);
+        Ok(self)
+    }
+
+    /// Adds dither ahead of compression to mask the reduced bit depth
+    pub fn dither(&mut self, dither: bool) -> &mut Self {
+        self.dither = dither;
+        self
+    }
+
+    /// Scales the sample so its loudest point reaches full scale
+    pub fn normalize(&mut self, normalize: bool) -> &mut Self {
+        self.normalize = normalize;
+        self
+    }
+
+    /// Removes rumble below the cutoff frequency in Hz, in the range 20-500
+    ///
+    /// Sub-bass wastes headroom and is inaudible on small speakers, cutoffs of
+    /// 30-120Hz are typical.
+    pub fn high_pass(&mut self, cutoff: u32) -> Result<&mut Self, SyroError> {
+        let (lo, hi) = HIGH_PASS_RANGE;
+        if !(lo..=hi).contains(&cutoff) {
+            return Err(SyroError::OutOfBounds {
+                val: cutoff,
+                name: HIGH_PASS_ERROR_NAME,
+                lo: lo as usize,
+                hi: hi as usize,
+            });
+        }
+        self.high_pass = Some(cutoff);
+        Ok(self)
+    }
+
+    /// Changes the length of the sample by the factor, from 0.5 to 2, while
+    /// keeping its pitch
+    #[cfg(feature = "stretch")]
+    pub fn time_stretch(&mut self, factor: f32) -> Result<&mut Self, SyroError> {
+        Stretch::Factor(factor).factor(1, 1)?;
+        self.stretch = Some(Stretch::Factor(factor));
+        Ok(self)
+    }
+
+    /// Time-stretches the sample to the given length, for example to match a
+    /// number of bars or fit the memory budget
+    ///
+    /// Adding the sample fails if that takes a factor outside of 0.5 to 2.
+    #[cfg(feature = "stretch")]
+    pub fn stretch_to(&mut self, duration: Duration) -> &mut Self {
+        self.stretch = Some(Stretch::Duration(duration));
+        self
+    }
+
+    /// Overrides the [rate_policy](SyroStream::rate_policy) of the stream
+    pub fn rate_policy(&mut self, policy: RatePolicy) -> &mut Self {
+        self.rate_policy = Some(policy);
+        self
+    }
+
+    pub fn build(self) -> Self {
+        self
+    }
+}
+
+/// Silence and fades added around a generated syro stream
+///
+/// Phones and audio interfaces often cut off the first fraction of a second of
+/// playback, which corrupts the start of the transfer. A lead-in of silence
+/// keeps the stream itself intact.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Padding {
+    /// Silence before the stream
+    pub lead_in: Duration,
+    /// Silence after the stream
+    pub lead_out: Duration,
+    /// Length of the linear fade in and out at the edges of the stream, keep
+    /// it to a few milliseconds to avoid clicks without affecting the transfer
+    pub ramp: Duration,
+}
+
+/// Frequency of the calibration tones in Hz, see [Calibration]
+pub const CALIBRATION_FREQUENCY: u32 = 1000;
+
+/// Level of the calibration reference tone in dBFS, see [Calibration]
+pub const CALIBRATION_LEVEL_DB: f32 = -20.0;
+
+/// A passage played before the syro stream for checking the cable and level
+/// chain, see [calibration](SyroStream::calibration)
+///
+/// The passage starts with a [CALIBRATION_FREQUENCY] sine at
+/// [CALIBRATION_LEVEL_DB] as a reference tone, followed by the same sine at
+/// the peak level of the syro data, taking the [gain](SyroStream::gain_db)
+/// into account. The reference tone should come through clean on the
+/// receiving end and the level check must not clip, otherwise the transfer
+/// is likely to fail. The calibration comes before the lead-in of the
+/// [padding](SyroStream::padding).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Calibration {
+    /// Length of the reference tone
+    pub tone: Duration,
+    /// Length of the tone at the peak level of the stream
+    pub level_check: Duration,
+    /// Silence after the level check
+    pub gap: Duration,
+}
+
+impl Default for Calibration {
+    fn default() -> Self {
+        Self {
+            tone: Duration::from_secs(1),
+            level_check: Duration::from_secs(1),
+            gap: Duration::from_millis(500),
+        }
+    }
+}
+
+impl Calibration {
+    fn total_frames(&self) -> usize {
+        frames(self.tone) + frames(self.level_check) + frames(self.gap)
+    }
+
+    // interleaved stereo PCM at the native rate
+    fn render(&self, gain: f32) -> Vec<i16> {
+        let reference = 10f32.powf(CALIBRATION_LEVEL_DB / 20.0);
+        let tone = frames(self.tone);
+        let level_check = frames(self.level_check);
+        let mut data = Vec::with_capacity(self.total_frames() * 2);
+        let sine = dsp::sine(
+            CALIBRATION_FREQUENCY,
+            OUTPUT_SAMPLE_RATE,
+            tone + level_check,
+        );
+        for (i, sample) in sine.into_iter().enumerate() {
+            let level = if i < tone { reference } else { gain };
+            let sample = (sample * level * i16::MAX as f32) as i16;
+            data.extend_from_slice(&[sample, sample]);
+        }
+        data.resize(self.total_frames() * 2, 0);
+        data
+    }
+}
+
 /// Builder struct for syrostream data.
 ///
 /// Output from the [generate](SyroStream::generate) or
 /// [reset](SyroStream::reset) methods is uncompressed PCM
 /// data that can be used to write a .wav file.
+#[derive(Clone)]
 pub struct SyroStream {
-    samples: [Option<SyroDataBundle>; 100],
-    patterns: [Option<SyroDataBundle>; 10],
+    // queued items in transfer order, see SlotId
+    slots: BTreeMap<SlotId, SyroDataBundle>,
+    rate_policy: RatePolicy,
+    padding: Padding,
+    calibration: Option<Calibration>,
+    output_rate: OutputRate,
+    gain_db: f32,
+}
+
+consuming_builder! {
+    /// Builder for a [SyroStream] whose methods take and return it by value,
+    /// created with [SyroStream::builder]
+    #[derive(Default)]
+    SyroStreamBuilder(SyroStream) {
+        fn rate_policy(policy: RatePolicy);
+        fn padding(padding: Padding);
+        fn calibration(calibration: Option<Calibration>);
+    } fallible {
+        fn output_rate(rate: OutputRate);
+        fn gain_db(gain_db: f32);
+        fn add_sample(index: u32, data: Vec<i16>, sample_rate: u32, compression: Option<u32>);
+        fn add_sample_with(index: u32, data: Vec<i16>, options: &AddSampleOptions);
+        fn erase_sample(index: u32);
+        fn move_sample(from: u32, to: u32);
+        fn swap_samples(a: u32, b: u32);
+        fn remap_part_samples(mapping: &[(u32, u32)]);
+        fn add_pattern(index: usize, pattern: pattern::Pattern);
+        fn clear_pattern(index: usize);
+        fn add_pattern_bank(patterns: impl IntoIterator<Item = pattern::Pattern>);
+    }
 }
 
 impl Default for SyroStream {
     fn default() -> Self {
         Self {
-            samples: array_init::array_init(|_| None),
-            patterns: array_init::array_init(|_| None),
+            slots: BTreeMap::new(),
+            rate_policy: RatePolicy::default(),
+            padding: Padding::default(),
+            calibration: None,
+            output_rate: OutputRate::default(),
+            gain_db: 0.0,
         }
     }
 }
@@ -220,31 +861,176 @@ fn convert_data(data: Vec<i16>) -> Vec<u8> {
 }
 
 impl SyroStream {
+    /// Starts a [SyroStreamBuilder] for building a stream in a single expression
+    pub fn builder() -> SyroStreamBuilder {
+        SyroStreamBuilder::default()
+    }
+
+    /// Creates a stream from (index, data, sample rate) entries, see
+    /// [add_sample](SyroStream::add_sample)
+    ///
+    /// Every entry is checked, failures are collected in
+    /// [SyroError::InvalidEntries] along with their position in the input.
+    pub fn from_samples<I>(samples: I) -> Result<Self, SyroError>
+    where
+        I: IntoIterator<Item = (u32, Vec<i16>, u32)>,
+    {
+        let mut stream = Self::default();
+        let mut errors = vec![];
+        for (entry, (index, data, sample_rate)) in samples.into_iter().enumerate() {
+            if let Err(err) = stream.add_sample(index, data, sample_rate, None) {
+                errors.push((entry, err));
+            }
+        }
+        stream.or_entry_errors(errors)
+    }
+
+    /// Creates a stream from (index, pattern) entries, reporting every
+    /// invalid entry like [from_samples](SyroStream::from_samples)
+    pub fn from_patterns<I>(patterns: I) -> Result<Self, SyroError>
+    where
+        I: IntoIterator<Item = (usize, pattern::Pattern)>,
+    {
+        let mut stream = Self::default();
+        let mut errors = vec![];
+        for (entry, (index, pattern)) in patterns.into_iter().enumerate() {
+            if let Err(err) = stream.add_pattern(index, pattern) {
+                errors.push((entry, err));
+            }
+        }
+        stream.or_entry_errors(errors)
+    }
+
+    fn or_entry_errors(self, errors: Vec<(usize, SyroError)>) -> Result<Self, SyroError> {
+        if errors.is_empty() {
+            Ok(self)
+        } else {
+            Err(SyroError::InvalidEntries { errors })
+        }
+    }
+
     /// Generate stream from a .alldata file
+    ///
+    /// Fails with [SyroError::EmptyStream] if the file is empty.
     pub fn reset(data: Vec<u8>, compression: Option<u32>) -> Result<Vec<i16>, SyroError> {
+        Self::from_alldata(data, compression)?.generate()
+    }
+
+    /// Creates a stream restoring a .alldata file, to be generated with any of
+    /// the generate methods, see [reset](SyroStream::reset)
+    ///
+    /// The file replaces the whole contents of the device, so nothing else
+    /// should be queued on the stream.
+    pub fn from_alldata(data: Vec<u8>, compression: Option<u32>) -> Result<Self, SyroError> {
+        if data.is_empty() {
+            return Err(SyroError::EmptyStream);
+        }
         let mut syro_stream = Self::default();
         let syro_data_bundle = match compression {
             Some(bit_depth) => {
-                check_bit_depth(bit_depth as u8)?;
-                SyroDataBundle::reset_compressed(data, bit_depth)
+                check_bit_depth(bit_depth.min(u8::MAX as u32) as u8)?;
+                SyroDataBundle::reset_compressed(data, bit_depth)?
             }
-            None => SyroDataBundle::reset(data),
+            None => SyroDataBundle::reset(data)?,
         };
-        match syro_stream.samples.get_mut(0) {
-            Some(elem) => {
-                *elem = Some(syro_data_bundle);
-            }
-            None => unreachable!(),
+        syro_stream
+            .slots
+            .insert(SlotId::Sample(0), syro_data_bundle);
+        Ok(syro_stream)
+    }
+
+    /// Sets how samples added after this call handle unsupported sample rates
+    ///
+    /// Defaults to [RatePolicy::Passthrough]
+    pub fn rate_policy(&mut self, policy: RatePolicy) -> &mut Self {
+        self.rate_policy = policy;
+        self
+    }
+
+    /// Sets the silence and fades added around the generated stream
+    pub fn padding(&mut self, padding: Padding) -> &mut Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Sets the calibration passage played before the generated stream,
+    /// defaults to None
+    pub fn calibration(&mut self, calibration: Option<Calibration>) -> &mut Self {
+        self.calibration = calibration;
+        self
+    }
+
+    /// Sets the sample rate of the generated audio, defaults to 44.1kHz
+    pub fn output_rate(&mut self, rate: OutputRate) -> Result<&mut Self, SyroError> {
+        let (lo, hi) = OUTPUT_RATE_RANGE;
+        if !(lo..=hi).contains(&rate.hz()) {
+            return Err(SyroError::OutOfBounds {
+                val: rate.hz(),
+                name: OUTPUT_RATE_ERROR_NAME,
+                lo: lo as usize,
+                hi: hi as usize,
+            });
+        }
+        self.output_rate = rate;
+        Ok(self)
+    }
+
+    /// Attenuates the generated audio by the given gain in dB, from
+    /// [MIN_GAIN_DB] to 0
+    ///
+    /// Some audio interfaces and phone outputs clip the full-scale signal,
+    /// 1-6 dB of headroom is usually enough to avoid that. The Volca Sample
+    /// still decodes attenuated signals, but the quieter the signal the more
+    /// it is exposed to noise, so use no more attenuation than needed.
+    pub fn gain_db(&mut self, gain_db: f32) -> Result<&mut Self, SyroError> {
+        if !(MIN_GAIN_DB..=0.0).contains(&gain_db) {
+            return Err(SyroError::GainOutOfRange { gain_db });
+        }
+        self.gain_db = gain_db;
+        Ok(self)
+    }
+
+    // the queued samples and erases in slot order
+    fn samples(&self) -> impl Iterator<Item = &SyroDataBundle> + '_ {
+        self.slots
+            .range(SlotId::Sample(0)..SlotId::Pattern(0))
+            .map(|(_, bundle)| bundle)
+    }
+
+    // the queued patterns in slot order
+    fn patterns(&self) -> impl Iterator<Item = &SyroDataBundle> + '_ {
+        self.slots
+            .range(SlotId::Pattern(0)..)
+            .map(|(_, bundle)| bundle)
+    }
+
+    fn sample(&self, index: u32) -> Option<&SyroDataBundle> {
+        self.slots.get(&SlotId::Sample(u8::try_from(index).ok()?))
+    }
+
+    // frames of the calibration passage at the native rate
+    fn prelude_frames(&self) -> usize {
+        self.calibration
+            .map_or(0, |calibration| calibration.total_frames())
+    }
+
+    // an empty stream with the same generation settings
+    fn empty_with_settings(&self) -> Self {
+        Self {
+            rate_policy: self.rate_policy,
+            padding: self.padding,
+            calibration: self.calibration,
+            output_rate: self.output_rate,
+            gain_db: self.gain_db,
+            ..Self::default()
         }
-        syro_stream.generate()
     }
 
     /// Add a sample at the given index
     ///
     /// The index must be in the range 0-99. If compression is desired it has to
-    /// be in the range of 8-16 bits.
-    ///
-    ///_**Note**: there are currently no guards against using samples that are too large._
+    /// be in the range of 8-16 bits. See [add_sample_with](SyroStream::add_sample_with)
+    /// for more settings.
     pub fn add_sample(
         &mut self,
         index: u32,
@@ -252,99 +1038,1104 @@ impl SyroStream {
         sample_rate: u32,
         compression: Option<u32>,
     ) -> Result<&mut Self, SyroError> {
-        check_sample_index(index as u8)?;
+        let mut options = AddSampleOptions::new(sample_rate);
+        if let Some(bit_depth) = compression {
+            options.compression(bit_depth)?;
+        }
+        self.add_sample_with(index, data, &options)
+    }
+
+    /// Add a sample at the given index with the given settings
+    ///
+    /// The index must be in the range 0-99. Samples larger than
+    /// [MAX_SAMPLE_SIZE] bytes as 16 bit PCM are rejected, smaller samples may
+    /// still not fit next to the samples already on the device. The sample rate
+    /// is handled according to the [rate_policy](SyroStream::rate_policy)
+    /// unless the options override it.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, data), fields(len = data.len()))
+    )]
+    pub fn add_sample_with(
+        &mut self,
+        index: u32,
+        data: Vec<i16>,
+        options: &AddSampleOptions,
+    ) -> Result<&mut Self, SyroError> {
+        SampleIndex::try_from(index)?;
+        let rate_policy = options.rate_policy.unwrap_or(self.rate_policy);
+        let (mut data, sample_rate) = rate_policy.apply(data, options.sample_rate)?;
+        #[cfg(feature = "stretch")]
+        if let Some(stretch) = options.stretch {
+            let factor = stretch.factor(data.len(), sample_rate)?;
+            data = dsp::time_stretch(&data, sample_rate, factor as f64);
+        }
+        let size = data.len().saturating_mul(2);
+        if size > MAX_SAMPLE_SIZE {
+            return Err(SyroError::SampleTooLarge { index, size });
+        }
+        if let Some(cutoff) = options.high_pass {
+            dsp::high_pass(&mut data, sample_rate, cutoff);
+        }
+        if options.normalize {
+            dsp::normalize(&mut data);
+        }
+        if let (true, Some(bit_depth)) = (options.dither, options.compression) {
+            dsp::dither(&mut data, bit_depth);
+        }
+
         let data = convert_data(data);
-        let bundle = match compression {
-            Some(bit_depth) => {
-                check_bit_depth(bit_depth as u8)?;
-                SyroDataBundle::sample(
-                    index,
-                    syro::SyroDataType::DataType_Sample_Compress,
-                    data,
-                    sample_rate,
-                    bit_depth,
-                )
-            }
+        let bundle = match options.compression {
+            Some(bit_depth) => SyroDataBundle::sample(
+                index,
+                syro::SyroDataType::DataType_Sample_Compress,
+                data,
+                sample_rate,
+                bit_depth,
+            )?,
             None => SyroDataBundle::sample(
                 index,
                 syro::SyroDataType::DataType_Sample_Liner,
                 data,
                 sample_rate,
                 0,
-            ),
+            )?,
         };
-        match self.samples.get_mut(index as usize) {
-            Some(elem) => *elem = Some(bundle),
-            None => panic!("Index out of bounds, checking must have failed"),
-        }
+        self.slots
+            .insert(SampleIndex::try_from(index)?.into(), bundle);
         Ok(self)
     }
 
-    /// Erase the sample at the given index
+    /// Adds a sample to the lowest slot not yet used by the stream and returns
+    /// the slot index
     ///
-    /// The index must be in the range 0-99
-    pub fn erase_sample(&mut self, index: u32) -> Result<&mut Self, SyroError> {
-        check_sample_index(index as u8)?;
-        // TODO maybe refactor to remove the check function and just throw on None
-        match self.samples.get_mut(index as usize) {
-            Some(elem) => *elem = Some(SyroDataBundle::erase(index)),
-            None => panic!("Index out of bounds, checking must have failed"),
-        }
-        Ok(self)
+    /// Slots holding a sample or an erase are skipped. Fails with
+    /// [SyroError::NoFreeSlot] once all 100 slots are used.
+    pub fn add_sample_auto(
+        &mut self,
+        data: Vec<i16>,
+        options: &AddSampleOptions,
+    ) -> Result<u32, SyroError> {
+        let index = (0..VolcaSample::SAMPLE_SLOTS as u32)
+            .find(|&index| self.sample(index).is_none())
+            .ok_or(SyroError::NoFreeSlot)?;
+        self.add_sample_with(index, data, options)?;
+        Ok(index)
+    }
+
+    /// Returns the PCM data and sample rate of the sample queued at the index
+    ///
+    /// None for slots without a sample, including erased slots.
+    pub fn sample_data(&self, index: u32) -> Option<(Vec<i16>, u32)> {
+        let bundle = self.sample(index)?;
+        match bundle.syro_data.DataType {
+            syro::SyroDataType::DataType_Sample_Liner
+            | syro::SyroDataType::DataType_Sample_Compress => {
+                let mut data = vec![0; bundle.data.len() / 2];
+                LittleEndian::read_i16_into(&bundle.data, &mut data);
+                Some((data, bundle.syro_data.Fs))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the queued slots in transfer order with the kind of data
+    /// queued for each
+    pub fn slots(&self) -> impl Iterator<Item = (SlotId, DataType)> + '_ {
+        self.slots
+            .iter()
+            .map(|(&slot, bundle)| (slot, data_type(bundle.syro_data.DataType)))
+    }
+
+    /// Summarizes the sample queued at the index, see [sample_data](SyroStream::sample_data)
+    pub fn sample_summary(&self, index: u32) -> Option<analysis::Summary> {
+        let (data, sample_rate) = self.sample_data(index)?;
+        Some(analysis::Summary::of(&data, sample_rate))
+    }
+
+    /// Waveform overview of the sample queued at the index, see [analysis::peaks]
+    pub fn sample_peaks(&self, index: u32, buckets: usize) -> Option<Vec<(i16, i16)>> {
+        let (data, _) = self.sample_data(index)?;
+        Some(analysis::peaks(&data, buckets))
+    }
+
+    /// Returns the size in bytes of the queued samples in device memory
+    pub fn sample_memory(&self) -> usize {
+        self.samples()
+            .filter(|bundle| {
+                matches!(
+                    bundle.syro_data.DataType,
+                    syro::SyroDataType::DataType_Sample_Liner
+                        | syro::SyroDataType::DataType_Sample_Compress
+                )
+            })
+            .map(|bundle| bundle.data.len())
+            .sum()
+    }
+
+    /// Downsamples queued samples until they fit in `budget` bytes of sample
+    /// memory
+    ///
+    /// Slots are lowered in the given priority order, each one only as far as
+    /// needed and at most down to [MIN_SAMPLE_RATE], so the first slots take
+    /// the most degradation. Compression only shortens the transfer and does
+    /// not free sample memory, so it is left as is. Nothing is changed when
+    /// the samples can not be made to fit.
+    pub fn fit_to_budget(
+        &mut self,
+        budget: usize,
+        priority: &[u32],
+    ) -> Result<Vec<Downsampled>, SyroError> {
+        let mut used = self.sample_memory();
+        let mut replacements = Vec::new();
+        for &index in priority {
+            SampleIndex::try_from(index)?;
+            if used <= budget {
+                break;
+            }
+            let (data, from_rate) = match self.sample_data(index) {
+                Some(sample) if !sample.0.is_empty() => sample,
+                _ => continue,
+            };
+            let frames = data.len();
+            let excess_frames = (used - budget).div_ceil(2);
+            let allowed = frames.saturating_sub(excess_frames);
+            let to_rate =
+                ((from_rate as u64 * allowed as u64 / frames as u64) as u32).max(MIN_SAMPLE_RATE);
+            if to_rate >= from_rate {
+                continue;
+            }
+            let data = dsp::resample(&data, from_rate, to_rate);
+            used = used - frames * 2 + data.len() * 2;
+            let downsampled = Downsampled {
+                index,
+                from_rate,
+                to_rate,
+                from_size: frames * 2,
+                to_size: data.len() * 2,
+            };
+            replacements.push((data, downsampled));
+        }
+        if used > budget {
+            return Err(SyroError::InsufficientMemory {
+                size: used,
+                available: budget,
+            });
+        }
+
+        let mut report = Vec::with_capacity(replacements.len());
+        for (data, downsampled) in replacements {
+            let slot = SlotId::from(SampleIndex::try_from(downsampled.index)?);
+            if let Some(bundle) = self.slots.get_mut(&slot) {
+                *bundle = SyroDataBundle::sample(
+                    downsampled.index,
+                    bundle.syro_data.DataType,
+                    convert_data(data),
+                    downsampled.to_rate,
+                    bundle.syro_data.Quality,
+                )?;
+            }
+            report.push(downsampled);
+        }
+        Ok(report)
+    }
+
+    /// Compresses queued samples until the stream fits in `limit`
+    ///
+    /// The transfer time includes the [padding](SyroStream::padding) and
+    /// [calibration](SyroStream::calibration). Samples
+    /// are lowered one bit at a time, always the one with the highest bit
+    /// depth first and the largest on ties, so quality degrades evenly across
+    /// the kit and no sample goes below `min_bit_depth`. Returns the samples
+    /// whose compression changed. Nothing is changed when the stream can not
+    /// be made to fit.
+    pub fn fit_transfer_time(
+        &mut self,
+        limit: Duration,
+        min_bit_depth: u32,
+    ) -> Result<Vec<Recompressed>, SyroError> {
+        check_bit_depth(min_bit_depth.min(u8::MAX as u32) as u8)?;
+        let mut data = self.syro_data()?;
+        let (handle, num_frames) = init_syro_handle(&mut data)?;
+        free_syro_handle(handle)?;
+        let padding =
+            self.prelude_frames() + frames(self.padding.lead_in) + frames(self.padding.lead_out);
+        let mut total = padding + num_frames as usize;
+        let limit_frames = frames(limit);
+
+        // uncompressed samples rank above 16 bit compression
+        let rank = |bit_depth: Option<u32>| bit_depth.unwrap_or(BIT_DEPTH_RANGE.1 as u32 + 1);
+        let mut candidates = vec![];
+        for item in data.iter().filter(|item| item.Size > 0) {
+            let bit_depth = match item.DataType {
+                syro::SyroDataType::DataType_Sample_Liner => None,
+                syro::SyroDataType::DataType_Sample_Compress => Some(item.Quality),
+                _ => continue,
+            };
+            let frames = item_frames(*item)?;
+            candidates.push((*item, bit_depth, frames));
+        }
+
+        while total > limit_frames {
+            let lowest = candidates
+                .iter_mut()
+                .filter(|(_, bit_depth, _)| rank(*bit_depth) > min_bit_depth)
+                .max_by_key(|(item, bit_depth, _)| (rank(*bit_depth), item.Size));
+            let (item, bit_depth, frames) = match lowest {
+                Some(candidate) => candidate,
+                None => {
+                    return Err(SyroError::TransferTooLong {
+                        duration: frames_duration(total, OUTPUT_SAMPLE_RATE),
+                        limit,
+                    })
+                }
+            };
+            let lowered = rank(*bit_depth) - 1;
+            item.DataType = syro::SyroDataType::DataType_Sample_Compress;
+            item.Quality = lowered;
+            let lowered_frames = item_frames(*item)?;
+            total = total + lowered_frames - *frames;
+            *bit_depth = Some(lowered);
+            *frames = lowered_frames;
+        }
+
+        let mut report = vec![];
+        for (item, bit_depth, _) in candidates {
+            let slot = SlotId::from(SampleIndex::try_from(item.Number)?);
+            let bundle = match self.slots.get_mut(&slot) {
+                Some(bundle) => bundle,
+                None => continue,
+            };
+            let from_bit_depth = match bundle.syro_data.DataType {
+                syro::SyroDataType::DataType_Sample_Compress => Some(bundle.syro_data.Quality),
+                _ => None,
+            };
+            if bit_depth == from_bit_depth {
+                continue;
+            }
+            // the data stays the same, only how it is encoded changes
+            bundle.syro_data.DataType = item.DataType;
+            bundle.syro_data.Quality = item.Quality;
+            report.push(Recompressed {
+                index: item.Number,
+                from_bit_depth,
+                to_bit_depth: item.Quality,
+            });
+        }
+        Ok(report)
+    }
+
+    /// Moves the sample or erase queued at `from` to the free slot `to`
+    ///
+    /// Patterns are left as is, use [remap_part_samples](SyroStream::remap_part_samples)
+    /// to point their parts at the new slot.
+    pub fn move_sample(&mut self, from: u32, to: u32) -> Result<&mut Self, SyroError> {
+        let from_slot = SlotId::from(SampleIndex::try_from(from)?);
+        let to_slot = SlotId::from(SampleIndex::try_from(to)?);
+        if from == to {
+            return match self.slots.contains_key(&from_slot) {
+                true => Ok(self),
+                false => Err(SyroError::SlotEmpty { slot: from_slot }),
+            };
+        }
+        if self.slots.contains_key(&to_slot) {
+            return Err(SyroError::SlotOccupied { slot: to_slot });
+        }
+        let mut bundle = self
+            .slots
+            .remove(&from_slot)
+            .ok_or(SyroError::SlotEmpty { slot: from_slot })?;
+        bundle.syro_data.Number = to;
+        self.slots.insert(to_slot, bundle);
+        Ok(self)
+    }
+
+    /// Swaps the samples or erases queued at the two slots, either may be empty
+    ///
+    /// Patterns are left as is, see [move_sample](SyroStream::move_sample).
+    pub fn swap_samples(&mut self, a: u32, b: u32) -> Result<&mut Self, SyroError> {
+        let a_slot = SlotId::from(SampleIndex::try_from(a)?);
+        let b_slot = SlotId::from(SampleIndex::try_from(b)?);
+        let a_bundle = self.slots.remove(&a_slot);
+        let b_bundle = self.slots.remove(&b_slot);
+        for (slot, index, bundle) in [(b_slot, b, a_bundle), (a_slot, a, b_bundle)] {
+            if let Some(mut bundle) = bundle {
+                bundle.syro_data.Number = index;
+                self.slots.insert(slot, bundle);
+            }
+        }
+        Ok(self)
+    }
+
+    /// Points the parts of the queued patterns at other samples
+    ///
+    /// Each `(from, to)` pair makes parts playing sample `from` play sample
+    /// `to`. The pairs are applied at once, so `[(1, 2), (2, 1)]` swaps the
+    /// samples of the parts.
+    pub fn remap_part_samples(&mut self, mapping: &[(u32, u32)]) -> Result<&mut Self, SyroError> {
+        for &(from, to) in mapping {
+            SampleIndex::try_from(from)?;
+            SampleIndex::try_from(to)?;
+        }
+        for (_, bundle) in self.slots.range_mut(SlotId::Pattern(0)..) {
+            let index = bundle.syro_data.Number;
+            let mut pattern = pattern::Pattern::from_bytes(&bundle.data)?;
+            let parts: Vec<_> = pattern.parts().collect();
+            for (part_index, part) in parts.iter().enumerate() {
+                let sample = part.sample_num() as u32;
+                if let Some(&(_, to)) = mapping.iter().find(|&&(from, _)| from == sample) {
+                    pattern.with_part(part_index as u8, part.clone_with_sample(to as u16)?)?;
+                }
+            }
+            *bundle = SyroDataBundle::pattern(index, pattern.to_bytes());
+        }
+        Ok(self)
+    }
+
+    /// Erase the sample at the given index
+    ///
+    /// The index must be in the range 0-99
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub fn erase_sample(&mut self, index: u32) -> Result<&mut Self, SyroError> {
+        self.slots.insert(
+            SampleIndex::try_from(index)?.into(),
+            SyroDataBundle::erase(index),
+        );
+        Ok(self)
     }
 
     /// Add a Pattern at the given index
     ///
     /// The index must be in the range 0-9
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, pattern))
+    )]
     pub fn add_pattern(
         &mut self,
         index: usize,
         pattern: pattern::Pattern,
     ) -> Result<&mut Self, SyroError> {
-        pattern::check_pattern_index(index as u8)?;
-        let data = SyroDataBundle::pattern(index as u32, pattern.to_bytes());
-        if let Some(elem) = self.patterns.get_mut(index) {
-            *elem = Some(data);
+        let slot = SlotId::from(PatternIndex::try_from(index)?);
+        self.slots.insert(
+            slot,
+            SyroDataBundle::pattern(index as u32, pattern.to_bytes()),
+        );
+        Ok(self)
+    }
+
+    /// Clears the pattern at the given index by sending a freshly initialized pattern
+    ///
+    /// The index must be in the range 0-9
+    pub fn clear_pattern(&mut self, index: usize) -> Result<&mut Self, SyroError> {
+        self.add_pattern(index, pattern::Pattern::default())
+    }
+
+    /// Adds all ten pattern slots at once, from slot 0 onwards
+    ///
+    /// Every pattern is validated first, and nothing is added if one of them
+    /// is invalid or more than ten patterns are given.
+    pub fn add_pattern_bank<I: IntoIterator<Item = pattern::Pattern>>(
+        &mut self,
+        patterns: I,
+    ) -> Result<&mut Self, SyroError> {
+        let mut bank = Vec::with_capacity(VolcaSample::PATTERN_SLOTS);
+        for (index, pattern) in patterns.into_iter().enumerate() {
+            let invalid = |source| SyroError::InvalidPatternInBank {
+                index,
+                source: Box::new(source),
+            };
+            PatternIndex::try_from(index).map_err(invalid)?;
+            pattern.validate().map_err(invalid)?;
+            bank.push(pattern);
+        }
+        for (index, pattern) in bank.into_iter().enumerate() {
+            self.add_pattern(index, pattern)?;
         }
         Ok(self)
     }
 
+    /// Checks that the parts of the added patterns play samples from the stream
+    ///
+    /// Only parts with active steps are checked. A sample reported as missing
+    /// may still be loaded on the device from an earlier transfer, while an
+    /// erased sample will be silent.
+    pub fn validate(&self) -> Vec<ValidationIssue> {
+        // a reset replaces every sample slot on the device
+        let reset = self.samples().any(|bundle| {
+            matches!(
+                bundle.syro_data.DataType,
+                syro::SyroDataType::DataType_Sample_All
+                    | syro::SyroDataType::DataType_Sample_AllCompress
+            )
+        });
+
+        let mut issues = vec![];
+        for bundle in self.patterns() {
+            let pattern = bundle.syro_data.Number as usize;
+            let parts = match pattern::Pattern::from_bytes(&bundle.data) {
+                Ok(parsed) => parsed.parts().collect::<Vec<_>>(),
+                Err(_) => continue,
+            };
+            for (part, data) in parts.iter().enumerate() {
+                if data.steps().to_bytes() == 0 {
+                    continue;
+                }
+                let sample = data.sample_num();
+                match self.sample(sample as u32) {
+                    Some(bundle)
+                        if bundle.syro_data.DataType
+                            == syro::SyroDataType::DataType_Sample_Erase =>
+                    {
+                        issues.push(ValidationIssue::ErasedSample {
+                            pattern,
+                            part,
+                            sample,
+                        });
+                    }
+                    Some(_) => {}
+                    _ if reset => {}
+                    _ => issues.push(ValidationIssue::MissingSample {
+                        pattern,
+                        part,
+                        sample,
+                    }),
+                }
+            }
+        }
+        issues
+    }
+
+    fn syro_data(&self) -> Result<Vec<syro::SyroData>, SyroError> {
+        let mut data: Vec<syro::SyroData> = Vec::with_capacity(110);
+        self.fill_syro_data(&mut data)?;
+        Ok(data)
+    }
+
+    // replaces the contents of data with the items of the stream
+    fn fill_syro_data(&self, data: &mut Vec<syro::SyroData>) -> Result<(), SyroError> {
+        data.clear();
+        data.extend(self.slots.values().map(SyroDataBundle::data));
+
+        for item in data.iter() {
+            validate_syro_data(item).map_err(|err| rejected(item, err))?;
+        }
+
+        if data.len() == 0 {
+            return Err(SyroError::EmptyStream);
+        }
+        Ok(())
+    }
+
     /// Generates the syro stream
     ///
     /// Ouptut is uncompressed PCM data
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "info", skip(self)))]
     pub fn generate(self) -> Result<Vec<i16>, SyroError> {
-        let mut data: Vec<syro::SyroData> = Vec::with_capacity(110);
+        let mut generator = self.generator()?;
+        let mut buffer = vec![0; generator.remaining_frames() * 2];
+        generator.fill(&mut buffer)?;
+        generator.end()?;
+        Ok(buffer)
+    }
 
-        for sample in self.samples.iter() {
-            if let Some(bundle) = sample {
-                data.push(bundle.data());
-            }
+    /// Generates the syro stream along with a [GenerationReport] on where
+    /// each item ends up in it
+    pub fn generate_with_report(self) -> Result<(Vec<i16>, GenerationReport), SyroError> {
+        let mut report = self.report()?;
+        let stream = self.generate()?;
+        report.frames = stream.len() / 2;
+        report.duration = frames_duration(report.frames, report.sample_rate);
+        Ok((stream, report))
+    }
+
+    // frame spans of the items at the output rate, the totals are filled in
+    // once the stream is generated
+    fn report(&self) -> Result<GenerationReport, SyroError> {
+        let mut data = self.syro_data()?;
+        let sample_rate = self.output_rate.hz();
+        let scale = |native: usize| {
+            (native as u64 * sample_rate as u64 / OUTPUT_SAMPLE_RATE as u64) as usize
+        };
+        let lead_in = self.prelude_frames() + frames(self.padding.lead_in);
+
+        let mut items = Vec::with_capacity(data.len());
+        let mut end = 0;
+        for count in 0..data.len() {
+            let item = data[count];
+            let start = end;
+            let (handle, num_frames) = init_syro_handle(&mut data[..=count])?;
+            free_syro_handle(handle)?;
+            end = num_frames as usize;
+
+            let (offset, frames) = (scale(lead_in + start), scale(end - start));
+            items.push(ItemReport {
+                slot: match data_type(item.DataType) {
+                    DataType::AllData | DataType::CompressedAllData => None,
+                    DataType::Pattern => Some(SlotId::Pattern(item.Number as u8)),
+                    _ => Some(SlotId::Sample(item.Number as u8)),
+                },
+                data_type: data_type(item.DataType),
+                bit_depth: match data_type(item.DataType) {
+                    DataType::CompressedSample | DataType::CompressedAllData => Some(item.Quality),
+                    _ => None,
+                },
+                offset,
+                frames,
+                start: frames_duration(offset, sample_rate),
+                duration: frames_duration(frames, sample_rate),
+            });
         }
+        Ok(GenerationReport {
+            frames: 0,
+            sample_rate,
+            duration: Duration::ZERO,
+            items,
+        })
+    }
 
-        for pattern in self.patterns.iter() {
-            if let Some(bundle) = pattern {
-                data.push(bundle.data());
-            }
+    /// Generates an independent syro stream for every sample and pattern
+    ///
+    /// Samples come first, followed by patterns, each in slot order. Every
+    /// stream can be played back on its own, so a failed transfer only has to
+    /// be retried for the affected slot.
+    pub fn generate_split(mut self) -> Result<Vec<(SlotId, Vec<i16>)>, SyroError> {
+        let mut streams = vec![];
+        for (slot, bundle) in std::mem::take(&mut self.slots) {
+            let mut syro_stream = self.empty_with_settings();
+            syro_stream.slots.insert(slot, bundle);
+            streams.push((slot, syro_stream.generate()?));
         }
 
-        if data.len() == 0 {
+        if streams.is_empty() {
             return Err(SyroError::EmptyStream);
         }
+        Ok(streams)
+    }
+
+    /// Generates the syro stream as the bytes of a WAV file
+    ///
+    /// The file holds 16 bit stereo PCM data at the
+    /// [output_rate](SyroStream::output_rate), ready to be played back to the
+    /// Volca Sample.
+    pub fn generate_wav(self) -> Result<Vec<u8>, SyroError> {
+        let sample_rate = self.output_rate.hz();
+        wav_bytes(&self.generate()?, sample_rate)
+    }
+
+    /// Generates the syro stream as a WAV file written to the sink
+    ///
+    /// Unlike [generate_wav](SyroStream::generate_wav) the stream is rendered
+    /// and written in fixed-size chunks, so memory use stays bounded for full
+    /// bank transfers of hundreds of megabytes. Wrap unbuffered sinks in a
+    /// [BufWriter](std::io::BufWriter) if they do many small writes.
+    pub fn generate_streaming<W: Write>(self, mut sink: W) -> Result<(), SyroError> {
+        const CHUNK_FRAMES: usize = 16384;
+
+        let mut generator = self.generator()?;
+        let header = wav_header(generator.remaining_frames() * 2, generator.sample_rate())?;
+        sink.write_all(&header)?;
+        let mut buffer = vec![0; CHUNK_FRAMES * 2];
+        let mut bytes = vec![0; CHUNK_FRAMES * 4];
+        loop {
+            let written = generator.fill(&mut buffer)?;
+            if written == 0 {
+                break;
+            }
+            LittleEndian::write_i16_into(&buffer[..written], &mut bytes[..written * 2]);
+            sink.write_all(&bytes[..written * 2])?;
+        }
+        generator.end()?;
+        sink.flush()?;
+        Ok(())
+    }
 
+    /// Generates the syro stream into a WAV file at the given path
+    ///
+    /// See [generate_streaming](SyroStream::generate_streaming)
+    pub fn generate_to_path<P: AsRef<Path>>(self, path: P) -> Result<(), SyroError> {
+        self.generate_streaming(File::create(path)?)
+    }
+
+    /// Generates the syro stream as separate left and right channel buffers
+    ///
+    /// Same as [generate](SyroStream::generate) but without interleaving
+    pub fn generate_planar(self) -> Result<(Vec<i16>, Vec<i16>), SyroError> {
+        let interleaved = self.generate()?;
+        let left = interleaved.iter().step_by(2).copied().collect();
+        let right = interleaved.iter().skip(1).step_by(2).copied().collect();
+        Ok((left, right))
+    }
+
+    /// Starts an incremental generation of the syro stream
+    ///
+    /// See [SyroGenerator] for rendering the PCM data into a reusable buffer.
+    pub fn generator(self) -> Result<SyroGenerator, SyroError> {
+        let mut data = self.syro_data()?;
+        let mut generator = self.start(&mut data, vec![])?;
+        generator._stream = Some(self);
+        Ok(generator)
+    }
+
+    // Starts a generator for the items in data, which must point into this
+    // stream. The caller keeps the stream alive until the generator is dropped.
+    fn start(
+        &self,
+        data: &mut [syro::SyroData],
+        native: Vec<i16>,
+    ) -> Result<SyroGenerator, SyroError> {
         // unsafe territory
-        let syro_stream = {
-            let (handle, num_frames) = init_syro_handle(data)?;
-            let result = generate_syro_stream(handle, num_frames);
-            free_syro_handle(handle)?;
-            result
-        }?;
-        Ok(syro_stream)
+        let (handle, num_frames) = init_syro_handle(data)?;
+        let stream_frames = num_frames as usize;
+        let gain = 10f32.powf(self.gain_db / 20.0);
+        let prelude = self
+            .calibration
+            .map_or(vec![], |calibration| calibration.render(gain));
+        let lead_in = frames(self.padding.lead_in);
+        let lead_out = frames(self.padding.lead_out);
+        let sample_rate = self.output_rate.hz();
+        let resampler = if sample_rate != OUTPUT_SAMPLE_RATE {
+            let native_frames = prelude.len() / 2 + lead_in + stream_frames + lead_out;
+            Some(dsp::Resampler::new(
+                OUTPUT_SAMPLE_RATE,
+                sample_rate,
+                native_frames,
+            ))
+        } else {
+            None
+        };
+        Ok(SyroGenerator {
+            handle: Some(handle),
+            prelude,
+            prelude_position: 0,
+            lead_in,
+            lead_out,
+            ramp: frames(self.padding.ramp).min(stream_frames / 2),
+            gain,
+            position: 0,
+            stream_frames,
+            sample_rate,
+            remaining_output: resampler.as_ref().map_or(0, |r| r.total_output()),
+            resampler,
+            native,
+            pending: vec![],
+            pending_position: 0,
+            _stream: None,
+        })
+    }
+}
+
+/// Incremental generator for syro stream data
+///
+/// Renders the stream block by block into caller provided buffers, for example
+/// the block of an audio callback, instead of allocating the whole stream at
+/// once. Created with [generator](SyroStream::generator).
+///
+/// The generator owns its SYRO handle, which is created, used and freed by it
+/// alone. It can be moved to another thread, but the handle is never shared,
+/// so no locking is needed around the C library.
+pub struct SyroGenerator {
+    // keeps the data referenced by the handle alive, None when the stream is
+    // borrowed for as long as the generator lives
+    _stream: Option<SyroStream>,
+    handle: Option<syro::SyroHandle>,
+    // calibration passage played before the lead-in
+    prelude: Vec<i16>,
+    prelude_position: usize,
+    // frames of silence left before and after the stream
+    lead_in: usize,
+    lead_out: usize,
+    ramp: usize,
+    // linear gain applied to the stream
+    gain: f32,
+    position: usize,
+    stream_frames: usize,
+    sample_rate: u32,
+    // set when the output rate differs from the native rate of the library
+    resampler: Option<dsp::Resampler>,
+    remaining_output: usize,
+    native: Vec<i16>,
+    pending: Vec<i16>,
+    pending_position: usize,
+}
+
+// SAFETY: the handle is owned by the generator and never shared
+unsafe impl Send for SyroGenerator {}
+
+impl SyroGenerator {
+    /// Fills the buffer with interleaved stereo PCM data
+    ///
+    /// Returns the number of samples written, which is less than the length of
+    /// the buffer at the end of the stream and 0 once the stream is exhausted.
+    /// Only whole frames are written, so an odd last sample is left untouched.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "trace", skip(self, buffer), fields(len = buffer.len()))
+    )]
+    pub fn fill(&mut self, buffer: &mut [i16]) -> Result<usize, SyroError> {
+        match self.resampler.take() {
+            Some(mut resampler) => {
+                let result = self.fill_resampled(&mut resampler, buffer);
+                self.resampler = Some(resampler);
+                result
+            }
+            None => self.fill_native(buffer),
+        }
+    }
+
+    fn fill_resampled(
+        &mut self,
+        resampler: &mut dsp::Resampler,
+        buffer: &mut [i16],
+    ) -> Result<usize, SyroError> {
+        const NATIVE_CHUNK_FRAMES: usize = 4096;
+
+        let wanted = self.remaining_output.min(buffer.len() / 2) * 2;
+        let mut native = std::mem::take(&mut self.native);
+        native.resize(NATIVE_CHUNK_FRAMES * 2, 0);
+        let mut written = 0;
+        while written < wanted {
+            if self.pending_position == self.pending.len() {
+                let read = self.fill_native(&mut native)?;
+                self.pending = if read > 0 {
+                    resampler.process(&native[..read])
+                } else {
+                    resampler.finish()
+                };
+                self.pending_position = 0;
+                if read == 0 && self.pending.is_empty() {
+                    break;
+                }
+            }
+            let pending = &self.pending[self.pending_position..];
+            let n = (wanted - written).min(pending.len());
+            buffer[written..written + n].copy_from_slice(&pending[..n]);
+            self.pending_position += n;
+            written += n;
+        }
+        self.native = native;
+        self.remaining_output -= written / 2;
+        Ok(written)
+    }
+
+    // fills the buffer at the native rate of the library
+    fn fill_native(&mut self, buffer: &mut [i16]) -> Result<usize, SyroError> {
+        let handle = match self.handle {
+            Some(handle) => handle,
+            None => return Ok(0),
+        };
+        let frames = self.native_remaining_frames().min(buffer.len() / 2);
+
+        let prelude = &self.prelude[self.prelude_position..];
+        let mut written = (prelude.len() / 2).min(frames);
+        buffer[..written * 2].copy_from_slice(&prelude[..written * 2]);
+        self.prelude_position += written * 2;
+
+        let lead_in = self.lead_in.min(frames - written);
+        buffer[written * 2..(written + lead_in) * 2].fill(0);
+        self.lead_in -= lead_in;
+        written += lead_in;
+
+        let stream = (self.stream_frames - self.position).min(frames - written);
+        if stream > 0 {
+            let chunk = &mut buffer[written * 2..(written + stream) * 2];
+            generate_syro_stream(handle, chunk)?;
+            self.apply_gain(chunk);
+            self.position += stream;
+            written += stream;
+        }
+
+        let lead_out = self.lead_out.min(frames - written);
+        buffer[written * 2..(written + lead_out) * 2].fill(0);
+        self.lead_out -= lead_out;
+        written += lead_out;
+
+        Ok(written * 2)
+    }
+
+    /// Returns the number of frames left to generate, including padding
+    pub fn remaining_frames(&self) -> usize {
+        if self.sample_rate == OUTPUT_SAMPLE_RATE {
+            self.native_remaining_frames()
+        } else {
+            self.remaining_output
+        }
+    }
+
+    /// Returns the sample rate of the generated audio
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn native_remaining_frames(&self) -> usize {
+        (self.prelude.len() - self.prelude_position) / 2 + self.lead_in + self.stream_frames
+            - self.position
+            + self.lead_out
+    }
+
+    /// Returns true once the whole stream has been generated
+    pub fn is_finished(&self) -> bool {
+        self.remaining_frames() == 0
+    }
+
+    // applies the gain and fades the frames at the edges of the stream, chunk
+    // starts at position
+    fn apply_gain(&self, chunk: &mut [i16]) {
+        if self.ramp == 0 && self.gain == 1.0 {
+            return;
+        }
+        for (i, frame) in chunk.chunks_exact_mut(2).enumerate() {
+            let position = self.position + i;
+            let edge = position.min(self.stream_frames - 1 - position);
+            let mut gain = self.gain;
+            if edge < self.ramp {
+                gain *= edge as f32 / self.ramp as f32;
+            }
+            for sample in frame {
+                *sample = (*sample as f32 * gain) as i16;
+            }
+        }
+    }
+
+    fn end(&mut self) -> Result<(), SyroError> {
+        match self.handle.take() {
+            Some(handle) => free_syro_handle(handle),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for SyroGenerator {
+    fn drop(&mut self) {
+        let _ = self.end();
     }
 }
 
-fn init_syro_handle(mut data: Vec<syro::SyroData>) -> Result<(syro::SyroHandle, u32), SyroError> {
+/// Summary of a generated stream, see [generate_with_report](SyroStream::generate_with_report)
+#[derive(Clone, Debug, PartialEq)]
+pub struct GenerationReport {
+    /// Stereo frames of the generated audio, including padding
+    pub frames: usize,
+    pub sample_rate: u32,
+    pub duration: Duration,
+    /// The samples and patterns in the order they are transferred
+    pub items: Vec<ItemReport>,
+}
+
+/// Position of a sample or pattern in the generated audio
+///
+/// SYRO doesn't mark where items start, so the spans are derived from the
+/// stream lengths of growing prefixes of the items. The first item includes
+/// the preamble of the stream.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ItemReport {
+    /// None for .alldata, which is not tied to a slot
+    pub slot: Option<SlotId>,
+    pub data_type: DataType,
+    /// The bit depth of compressed samples
+    pub bit_depth: Option<u32>,
+    /// First frame of the item in the generated audio
+    pub offset: usize,
+    pub frames: usize,
+    pub start: Duration,
+    pub duration: Duration,
+}
+
+/// Length of the syro stream for a sample compressed to a bit depth, see
+/// [estimate_compressed]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CompressionEstimate {
+    pub bit_depth: u32,
+    /// Stereo frames at [OUTPUT_SAMPLE_RATE]
+    pub frames: usize,
+    /// Playback time of the stream, without padding
+    pub duration: Duration,
+}
+
+/// Runs the SYRO compressor on the PCM data to estimate the stream length at
+/// the bit depth, from 8 to 16
+///
+/// No audio is generated, so this is cheap enough to compare settings before
+/// calling [add_sample](SyroStream::add_sample).
+pub fn estimate_compressed(data: &[i16], bit_depth: u32) -> Result<CompressionEstimate, SyroError> {
+    check_bit_depth(bit_depth.min(u8::MAX as u32) as u8)?;
+    // the sample rate is only stored on the device, it doesn't change the stream
+    let bundle = SyroDataBundle::sample(
+        0,
+        syro::SyroDataType::DataType_Sample_Compress,
+        convert_data(data.to_vec()),
+        OUTPUT_SAMPLE_RATE,
+        bit_depth,
+    )?;
+    let (handle, num_frames) = init_syro_handle(&mut [bundle.data()])?;
+    free_syro_handle(handle)?;
+    let frames = num_frames as usize;
+    Ok(CompressionEstimate {
+        bit_depth,
+        frames,
+        duration: Duration::from_secs_f64(frames as f64 / OUTPUT_SAMPLE_RATE as f64),
+    })
+}
+
+/// Estimates the stream length for every bit depth from 8 to 16, see
+/// [estimate_compressed]
+pub fn compression_estimates(data: &[i16]) -> Result<Vec<CompressionEstimate>, SyroError> {
+    (BIT_DEPTH_RANGE.0..=BIT_DEPTH_RANGE.1)
+        .map(|bit_depth| estimate_compressed(data, bit_depth as u32))
+        .collect()
+}
+
+/// Joins generated syro streams into a single stream
+///
+/// Every stream is separated from the next by `gap` of silence, giving the
+/// device time to finish a transfer before the next one starts. This allows
+/// e.g. erasing all samples, loading a kit and then patterns from one file.
+/// The gap length assumes streams at the default [OUTPUT_SAMPLE_RATE].
+pub fn concat(streams: &[Vec<i16>], gap: Duration) -> Vec<i16> {
+    let gap_frames = frames(gap);
+    let gaps = streams.len().saturating_sub(1) * gap_frames * 2;
+    let mut output = Vec::with_capacity(streams.iter().map(Vec::len).sum::<usize>() + gaps);
+    for (i, stream) in streams.iter().enumerate() {
+        if i > 0 {
+            output.resize(output.len() + gap_frames * 2, 0);
+        }
+        output.extend_from_slice(stream);
+    }
+    output
+}
+
+// number of output frames covering the duration
+fn frames(duration: Duration) -> usize {
+    (duration.as_secs_f64() * OUTPUT_SAMPLE_RATE as f64).round() as usize
+}
+
+fn frames_duration(frames: usize, sample_rate: u32) -> Duration {
+    Duration::from_secs_f64(frames as f64 / sample_rate as f64)
+}
+
+// Writes a RIFF header for 16 bit stereo PCM followed by the data
+fn wav_bytes(data: &[i16], sample_rate: u32) -> Result<Vec<u8>, SyroError> {
+    let header = wav_header(data.len(), sample_rate)?;
+    let mut bytes = vec![0; 44 + data.len() * 2];
+    bytes[..44].copy_from_slice(&header);
+    LittleEndian::write_i16_into(data, &mut bytes[44..]);
+    Ok(bytes)
+}
+
+// RIFF header for the given number of 16 bit stereo PCM samples, fails if the
+// sizes do not fit in the 32 bit fields
+fn wav_header(samples: usize, sample_rate: u32) -> Result<[u8; 44], SyroError> {
+    const CHANNELS: u16 = 2;
+    const BITS_PER_SAMPLE: u16 = 16;
+    const BLOCK_ALIGN: u16 = CHANNELS * BITS_PER_SAMPLE / 8;
+
+    let too_large = || SyroError::WavTooLarge { samples };
+    let data_size = u32::try_from(samples)
+        .ok()
+        .and_then(|samples| samples.checked_mul(2))
+        .ok_or_else(too_large)?;
+    let riff_size = data_size.checked_add(36).ok_or_else(too_large)?;
+    let mut bytes = [0; 44];
+    bytes[0..4].copy_from_slice(b"RIFF");
+    LittleEndian::write_u32(&mut bytes[4..8], riff_size);
+    bytes[8..12].copy_from_slice(b"WAVE");
+    bytes[12..16].copy_from_slice(b"fmt ");
+    LittleEndian::write_u32(&mut bytes[16..20], 16);
+    // PCM
+    LittleEndian::write_u16(&mut bytes[20..22], 1);
+    LittleEndian::write_u16(&mut bytes[22..24], CHANNELS);
+    LittleEndian::write_u32(&mut bytes[24..28], sample_rate);
+    LittleEndian::write_u32(&mut bytes[28..32], sample_rate * BLOCK_ALIGN as u32);
+    LittleEndian::write_u16(&mut bytes[32..34], BLOCK_ALIGN);
+    LittleEndian::write_u16(&mut bytes[34..36], BITS_PER_SAMPLE);
+    bytes[36..40].copy_from_slice(b"data");
+    LittleEndian::write_u32(&mut bytes[40..44], data_size);
+    Ok(bytes)
+}
+
+fn data_type(data_type: syro::SyroDataType) -> DataType {
+    match data_type {
+        syro::SyroDataType::DataType_Sample_Liner => DataType::Sample,
+        syro::SyroDataType::DataType_Sample_Compress => DataType::CompressedSample,
+        syro::SyroDataType::DataType_Sample_Erase => DataType::SampleErase,
+        syro::SyroDataType::DataType_Sample_All => DataType::AllData,
+        syro::SyroDataType::DataType_Sample_AllCompress => DataType::CompressedAllData,
+        syro::SyroDataType::DataType_Pattern => DataType::Pattern,
+    }
+}
+
+// wraps the error with the slot and data type of the item, .alldata is not
+// tied to a slot and is left as is
+fn rejected(item: &syro::SyroData, err: SyroError) -> SyroError {
+    let slot = match data_type(item.DataType) {
+        DataType::AllData | DataType::CompressedAllData => return err,
+        DataType::Pattern => SlotId::Pattern(item.Number as u8),
+        _ => SlotId::Sample(item.Number as u8),
+    };
+    SyroError::SlotRejected {
+        slot,
+        data_type: data_type(item.DataType),
+        source: Box::new(err),
+    }
+}
+
+// checks what the SYRO library would reject, ahead of starting it
+fn validate_syro_data(item: &syro::SyroData) -> Result<(), SyroError> {
+    let number = u64::from(item.Number);
+    match data_type(item.DataType) {
+        DataType::Pattern => {
+            PatternIndex::try_from(number)?;
+        }
+        DataType::Sample | DataType::CompressedSample | DataType::SampleErase => {
+            SampleIndex::try_from(number)?;
+        }
+        DataType::AllData | DataType::CompressedAllData => {}
+    }
+    match data_type(item.DataType) {
+        DataType::CompressedSample | DataType::CompressedAllData => {
+            check_bit_depth(item.Quality.min(u8::MAX as u32) as u8)?;
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+// starts the library on every item alone to find the one it rejects
+fn find_rejected(data: &[syro::SyroData]) -> Option<SyroError> {
+    data.iter().find_map(|item| {
+        let mut item = *item;
+        let mut handle: MaybeUninit<syro::SyroHandle> = MaybeUninit::uninit();
+        let mut num_frames = 0;
+        let status = unsafe {
+            syro::SyroVolcaSample_Start(handle.as_mut_ptr(), &mut item, 1, 0, &mut num_frames)
+        };
+        match check_syro_status(status) {
+            Ok(()) => {
+                let _ = free_syro_handle(unsafe { handle.assume_init() });
+                None
+            }
+            Err(err) => Some(rejected(&item, err)),
+        }
+    })
+}
+
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip(data), fields(items = data.len()))
+)]
+fn init_syro_handle(data: &mut [syro::SyroData]) -> Result<(syro::SyroHandle, u32), SyroError> {
     let mut num_frames = 0;
 
+    #[cfg(feature = "tracing")]
+    for item in data.iter() {
+        tracing::debug!(
+            data_type = ?item.DataType,
+            number = item.Number,
+            size = item.Size,
+            "encoding item"
+        );
+    }
+
     let handle: syro::SyroHandle = unsafe {
         let mut handle: MaybeUninit<syro::SyroHandle> = MaybeUninit::uninit();
 
@@ -355,14 +2146,28 @@ fn init_syro_handle(mut data: Vec<syro::SyroData>) -> Result<(syro::SyroHandle,
             0,
             &mut num_frames,
         );
-        check_syro_status(status)?;
+        if let Err(err) = check_syro_status(status) {
+            return Err(match &*data {
+                [item] => rejected(item, err),
+                items => find_rejected(items).unwrap_or(err),
+            });
+        }
 
         handle.assume_init()
     };
 
+    #[cfg(feature = "tracing")]
+    tracing::debug!(num_frames, "syro handle started");
     Ok((handle, num_frames))
 }
 
+// frames of a stream holding only the item
+fn item_frames(mut item: syro::SyroData) -> Result<usize, SyroError> {
+    let (handle, num_frames) = init_syro_handle(std::slice::from_mut(&mut item))?;
+    free_syro_handle(handle)?;
+    Ok(num_frames as usize)
+}
+
 fn free_syro_handle(handle: syro::SyroHandle) -> Result<(), SyroError> {
     unsafe {
         let status = korg_syro_sys::SyroVolcaSample_End(handle);
@@ -370,24 +2175,22 @@ fn free_syro_handle(handle: syro::SyroHandle) -> Result<(), SyroError> {
     }
 }
 
-fn generate_syro_stream(handle: syro::SyroHandle, num_frames: u32) -> Result<Vec<i16>, SyroError> {
-    let mut left: i16 = 0;
-    let mut right: i16 = 0;
-    let mut buffer = Vec::with_capacity(num_frames as usize * 2);
-    for _ in 0..num_frames {
-        unsafe {
-            let status = syro::SyroVolcaSample_GetSample(handle, &mut left, &mut right);
-            if status == syro::SyroStatus::Status_NoData {
-                // TODO investigate why GetSample keeps returning NoData and if it's ok
-            } else {
-                check_syro_status(status)?;
-            }
+fn generate_syro_stream(handle: syro::SyroHandle, buffer: &mut [i16]) -> Result<(), SyroError> {
+    // the SYRO API hands out a single frame per call, there is no batch variant
+    for frame in buffer.chunks_exact_mut(2) {
+        let mut left: i16 = 0;
+        let mut right: i16 = 0;
+        let status = unsafe { syro::SyroVolcaSample_GetSample(handle, &mut left, &mut right) };
+        match status {
+            syro::SyroStatus::Status_Success => {}
+            // TODO investigate why GetSample keeps returning NoData and if it's ok
+            syro::SyroStatus::Status_NoData => {}
+            status => check_syro_status(status)?,
         }
-        buffer.push(left);
-        buffer.push(right);
+        frame.copy_from_slice(&[left, right]);
     }
 
-    Ok(buffer)
+    Ok(())
 }
 
 #[cfg(test)]
@@ -421,6 +2224,693 @@ mod tests {
                 hi: 99
             }
         );
+        assert!(syro_stream.erase_sample(100).is_err());
+        assert!(syro_stream.add_pattern(10, Default::default()).is_err());
+
+        // values that wrap around when truncated to a byte
+        assert!(AddSampleOptions::default().compression(264).is_err());
+        assert!(SyroStream::reset(vec![0; 4], Some(264)).is_err());
+        assert!(pattern::Part::for_sample(300).is_err());
+        assert_eq!(SyroStream::reset(vec![], None), Err(SyroError::EmptyStream));
+    }
+
+    #[test]
+    fn sample_too_large() {
+        let mut syro_stream = SyroStream::default();
+        let result = syro_stream.add_sample(3, vec![0; MAX_SAMPLE_SIZE / 2 + 1], 44100, None);
+        assert_eq!(
+            result.err().unwrap(),
+            SyroError::SampleTooLarge {
+                index: 3,
+                size: MAX_SAMPLE_SIZE + 2
+            }
+        );
+        assert!(syro_stream
+            .add_sample(3, vec![0; MAX_SAMPLE_SIZE / 2], 44100, None)
+            .is_ok());
+    }
+
+    #[test]
+    fn rate_policy() -> anyhow::Result<()> {
+        let mut syro_stream = SyroStream::default();
+        syro_stream.add_sample(0, vec![0; 4], 96000, None)?;
+        assert_eq!(syro_stream.sample(0).unwrap().syro_data.Fs, 96000);
+
+        syro_stream.rate_policy(RatePolicy::Strict);
+        assert_eq!(
+            syro_stream.add_sample(1, vec![0; 4], 96000, None).err(),
+            Some(SyroError::OutOfBounds {
+                val: 96000,
+                name: "sample_rate",
+                lo: 8000,
+                hi: 48000
+            })
+        );
+        syro_stream.add_sample(1, vec![0; 4], 44100, None)?;
+
+        syro_stream.rate_policy(RatePolicy::Resample);
+        syro_stream.add_sample(2, vec![0; 4], 96000, None)?;
+        let bundle = syro_stream.sample(2).unwrap();
+        assert_eq!(bundle.syro_data.Fs, 48000);
+        assert_eq!(bundle.data.len(), 4);
+
+        syro_stream.rate_policy(RatePolicy::Standard);
+        syro_stream.add_sample(3, vec![0; 40], 30000, None)?;
+        let bundle = syro_stream.sample(3).unwrap();
+        assert_eq!(bundle.syro_data.Fs, 31250);
+        assert_eq!(bundle.data.len(), 2 * 42);
+        Ok(())
+    }
+
+    #[test]
+    fn sample_rate() {
+        assert_eq!(SampleRate::try_from(31250), Ok(SampleRate::Hz31250));
+        assert_eq!(
+            SampleRate::try_from(30000),
+            Err(SyroError::UnsupportedSampleRate { rate: 30000 })
+        );
+        assert_eq!(SampleRate::closest(0), SampleRate::Hz8000);
+        assert_eq!(SampleRate::closest(31625), SampleRate::Hz32000);
+        assert_eq!(SampleRate::closest(96000), SampleRate::Hz48000);
+        assert_eq!(u32::from(SampleRate::Hz22050), 22050);
+        assert_eq!(SampleRate::Hz44100.to_string(), "44100 Hz");
+    }
+
+    #[test]
+    fn add_sample_with() -> anyhow::Result<()> {
+        let mut syro_stream = SyroStream::default();
+        syro_stream.add_sample_with(
+            0,
+            vec![0, 1000, -2000],
+            AddSampleOptions::new(32000)
+                .compression(12)?
+                .normalize(true)
+                .rate_policy(RatePolicy::Strict),
+        )?;
+        let bundle = syro_stream.sample(0).unwrap();
+        assert_eq!(bundle.syro_data.Fs, 32000);
+        assert_eq!(bundle.syro_data.Quality, 12);
+        assert_eq!(bundle.data, convert_data(vec![0, 16384, -32767]));
+
+        assert!(AddSampleOptions::default().compression(17).is_err());
+        assert!(AddSampleOptions::default().high_pass(10).is_err());
+        assert!(syro_stream
+            .add_sample_with(
+                1,
+                vec![0],
+                AddSampleOptions::new(96000).rate_policy(RatePolicy::Strict)
+            )
+            .is_err());
+        Ok(())
+    }
+
+    #[cfg(feature = "stretch")]
+    #[test]
+    fn time_stretch() -> anyhow::Result<()> {
+        let mut syro_stream = SyroStream::default();
+        syro_stream.add_sample_with(
+            0,
+            vec![1000; 31250],
+            AddSampleOptions::new(31250).stretch_to(Duration::from_millis(750)),
+        )?;
+        let bundle = syro_stream.sample(0).unwrap();
+        assert_eq!(bundle.data.len(), 2 * 23438);
+
+        assert!(AddSampleOptions::default().time_stretch(2.5).is_err());
+        let err = syro_stream
+            .add_sample_with(
+                1,
+                vec![1000; 31250],
+                AddSampleOptions::new(31250).stretch_to(Duration::from_secs(3)),
+            )
+            .err();
+        assert_eq!(err, Some(SyroError::StretchOutOfRange { factor: 3.0 }));
+        Ok(())
+    }
+
+    #[test]
+    fn fit_to_budget() -> anyhow::Result<()> {
+        let mut syro_stream = SyroStream::default();
+        syro_stream
+            .add_sample(0, vec![100; 32000], 32000, None)?
+            .add_sample(1, vec![100; 32000], 32000, Some(8))?
+            .add_sample(2, vec![100; 16000], 16000, None)?;
+        assert_eq!(syro_stream.sample_memory(), 160000);
+
+        let report = syro_stream.fit_to_budget(100000, &[2, 1, 0])?;
+        assert_eq!(
+            report,
+            vec![
+                Downsampled {
+                    index: 2,
+                    from_rate: 16000,
+                    to_rate: 8000,
+                    from_size: 32000,
+                    to_size: 16000,
+                },
+                Downsampled {
+                    index: 1,
+                    from_rate: 32000,
+                    to_rate: 10000,
+                    from_size: 64000,
+                    to_size: 20000,
+                },
+            ]
+        );
+        assert_eq!(syro_stream.sample_memory(), 100000);
+        let bundle = syro_stream.sample(1).unwrap();
+        assert_eq!(bundle.syro_data.Quality, 8);
+        assert!(syro_stream.fit_to_budget(100000, &[])?.is_empty());
+
+        let result = syro_stream.fit_to_budget(10000, &[0]);
+        assert_eq!(
+            result,
+            Err(SyroError::InsufficientMemory {
+                size: 16000 + 20000 + 16000,
+                available: 10000
+            })
+        );
+        assert_eq!(syro_stream.sample_memory(), 100000);
+        Ok(())
+    }
+
+    #[test]
+    fn fit_transfer_time() -> anyhow::Result<()> {
+        let stream = || -> Result<SyroStream, SyroError> {
+            let mut syro_stream = SyroStream::default();
+            // noise, so every bit of depth dropped shortens the stream
+            let noise = |len: usize| -> Vec<i16> {
+                let mut rng = dsp::Rng(7);
+                (0..len)
+                    .map(|_| rng.jitter(i16::MAX as u32) as i16)
+                    .collect()
+            };
+            syro_stream
+                .add_sample(0, noise(40000), 44100, None)?
+                .add_sample(1, noise(20000), 44100, Some(14))?;
+            Ok(syro_stream)
+        };
+        let full = stream()?.generate()?.len() / 2;
+        let limit = Duration::from_secs_f64(full as f64 * 0.8 / 44100.0);
+
+        let mut syro_stream = stream()?;
+        let report = syro_stream.fit_transfer_time(limit, 10)?;
+        assert_eq!(report.len(), 2);
+        assert_eq!(report[0].index, 0);
+        assert_eq!(report[0].from_bit_depth, None);
+        assert_eq!(report[1].from_bit_depth, Some(14));
+        assert!(report.iter().all(|r| r.to_bit_depth >= 10));
+        // both end up within a bit of each other
+        assert!((report[0].to_bit_depth as i32 - report[1].to_bit_depth as i32).abs() <= 1);
+        assert!(syro_stream.generate()?.len() / 2 <= full * 8 / 10 + 1);
+
+        let mut syro_stream = stream()?;
+        assert!(syro_stream.fit_transfer_time(limit * 2, 8)?.is_empty());
+        assert!(matches!(
+            syro_stream.fit_transfer_time(limit, 15),
+            Err(SyroError::TransferTooLong { .. })
+        ));
+        assert_eq!(syro_stream.generate()?.len() / 2, full);
+        assert!(stream()?.fit_transfer_time(limit, 7).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn estimate_compressed() -> anyhow::Result<()> {
+        let data = sine_wave();
+        let estimate = super::estimate_compressed(&data, 12)?;
+        assert_eq!(estimate.bit_depth, 12);
+
+        let mut syro_stream = SyroStream::default();
+        syro_stream.add_sample(0, data.clone(), 44100, Some(12))?;
+        assert_eq!(syro_stream.generate()?.len(), estimate.frames * 2);
+        assert_eq!(
+            estimate.duration,
+            Duration::from_secs_f64(estimate.frames as f64 / 44100.0)
+        );
+
+        let estimates = compression_estimates(&data)?;
+        assert_eq!(estimates.len(), 9);
+        assert_eq!(estimates[4], estimate);
+        assert!(super::estimate_compressed(&data, 17).is_err());
+        assert!(super::estimate_compressed(&data, 264).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn move_samples() -> anyhow::Result<()> {
+        let mut syro_stream = SyroStream::default();
+        syro_stream
+            .add_sample(1, vec![1; 4], 44100, None)?
+            .add_sample(2, vec![2; 4], 44100, None)?
+            .erase_sample(3)?
+            .add_pattern(
+                0,
+                Pattern::from_parts(vec![(0, Part::for_sample(1)?), (1, Part::for_sample(2)?)])?,
+            )?;
+
+        syro_stream.move_sample(1, 5)?.swap_samples(2, 3)?;
+        assert!(syro_stream.sample(1).is_none());
+        assert_eq!(syro_stream.sample_data(5), Some((vec![1; 4], 44100)));
+        assert_eq!(syro_stream.sample_data(3), Some((vec![2; 4], 44100)));
+        assert_eq!(syro_stream.sample(2).unwrap().syro_data.Number, 2);
+        assert_eq!(syro_stream.sample(3).unwrap().syro_data.Number, 3);
+
+        assert_eq!(
+            syro_stream.move_sample(1, 6).err(),
+            Some(SyroError::SlotEmpty {
+                slot: SlotId::Sample(1)
+            })
+        );
+        assert_eq!(
+            syro_stream.move_sample(6, 6).err(),
+            Some(SyroError::SlotEmpty {
+                slot: SlotId::Sample(6)
+            })
+        );
+        syro_stream.move_sample(5, 5)?;
+        assert_eq!(
+            syro_stream.move_sample(5, 2).err(),
+            Some(SyroError::SlotOccupied {
+                slot: SlotId::Sample(2)
+            })
+        );
+        assert!(syro_stream.swap_samples(0, 100).is_err());
+
+        syro_stream.remap_part_samples(&[(1, 5), (2, 3)])?;
+        let bundle = &syro_stream.slots[&SlotId::Pattern(0)];
+        let samples: Vec<u16> = Pattern::from_bytes(&bundle.data)?
+            .parts()
+            .map(|part| part.sample_num())
+            .collect();
+        assert_eq!(&samples[..2], &[5, 3]);
+        syro_stream.generate()?;
+        Ok(())
+    }
+
+    #[test]
+    fn from_samples() -> anyhow::Result<()> {
+        let syro_stream =
+            SyroStream::from_samples(vec![(0, vec![1; 4], 44100), (7, vec![2; 4], 31250)])?;
+        assert_eq!(syro_stream.sample_data(7), Some((vec![2; 4], 31250)));
+
+        let result = SyroStream::from_samples(vec![
+            (0, vec![1; 4], 44100),
+            (100, vec![2; 4], 44100),
+            (3, vec![0; MAX_SAMPLE_SIZE], 44100),
+        ]);
+        let err = result.err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "2 invalid entries: entry 1: {}; entry 2: {}",
+                SampleIndex::try_from(100u32).unwrap_err(),
+                SyroError::SampleTooLarge {
+                    index: 3,
+                    size: 2 * MAX_SAMPLE_SIZE
+                }
+            )
+        );
+
+        let syro_stream = SyroStream::from_patterns(vec![(2, Pattern::default())])?;
+        assert!(syro_stream.slots.contains_key(&SlotId::Pattern(2)));
+        assert!(matches!(
+            SyroStream::from_patterns(vec![(10, Pattern::default())]),
+            Err(SyroError::InvalidEntries { errors }) if errors.len() == 1 && errors[0].0 == 0
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn generate_with_report() -> anyhow::Result<()> {
+        let mut syro_stream = SyroStream::default();
+        syro_stream
+            .padding(Padding {
+                lead_in: Duration::from_secs(1),
+                ..Padding::default()
+            })
+            .add_sample(3, vec![0; 4], 44100, Some(8))?
+            .add_pattern(1, Pattern::default())?;
+        let (stream, report) = syro_stream.generate_with_report()?;
+
+        assert_eq!(report.frames, stream.len() / 2);
+        assert_eq!(report.sample_rate, 44100);
+        assert_eq!(
+            report.duration,
+            Duration::from_secs_f64(report.frames as f64 / 44100.0)
+        );
+        assert_eq!(report.items.len(), 2);
+        let (sample, pattern) = (&report.items[0], &report.items[1]);
+        assert_eq!(sample.slot, Some(SlotId::Sample(3)));
+        assert_eq!(sample.data_type, DataType::CompressedSample);
+        assert_eq!(sample.bit_depth, Some(8));
+        assert_eq!(sample.offset, 44100);
+        assert_eq!(sample.start, Duration::from_secs(1));
+        assert_eq!(pattern.slot, Some(SlotId::Pattern(1)));
+        assert_eq!(pattern.bit_depth, None);
+        assert_eq!(pattern.offset, sample.offset + sample.frames);
+        assert_eq!(pattern.offset + pattern.frames, report.frames);
+        Ok(())
+    }
+
+    #[test]
+    fn clear_pattern() -> anyhow::Result<()> {
+        let mut syro_stream = SyroStream::default();
+        syro_stream.clear_pattern(9)?;
+        let bundle = &syro_stream.slots[&SlotId::Pattern(9)];
+        assert_eq!(bundle.data, Pattern::default().to_bytes());
+        assert!(syro_stream.clear_pattern(10).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn slots() -> anyhow::Result<()> {
+        let mut syro_stream = SyroStream::default();
+        syro_stream
+            .add_pattern(2, Pattern::default())?
+            .add_sample(40, vec![1; 4], 44100, Some(8))?
+            .erase_sample(3)?;
+        assert_eq!(
+            syro_stream.slots().collect::<Vec<_>>(),
+            [
+                (SlotId::Sample(3), DataType::SampleErase),
+                (SlotId::Sample(40), DataType::CompressedSample),
+                (SlotId::Pattern(2), DataType::Pattern),
+            ]
+        );
+
+        // the clone owns its data, so it outlives the original
+        let mut copy = syro_stream.clone();
+        copy.erase_sample(40)?;
+        let expected = syro_stream.clone().generate()?;
+        assert_eq!(syro_stream.sample_data(40), Some((vec![1; 4], 44100)));
+        drop(syro_stream);
+        assert_eq!(copy.slots().count(), 3);
+        assert_ne!(copy.generate()?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn add_pattern_bank() -> anyhow::Result<()> {
+        let mut syro_stream = SyroStream::default();
+        syro_stream.add_pattern_bank(array_init::array_init::<[Pattern; 10], _>(|_| {
+            Pattern::default()
+        }))?;
+        assert_eq!(syro_stream.patterns().count(), 10);
+
+        let mut syro_stream = SyroStream::default();
+        let mut invalid = Pattern::default().to_bytes();
+        // sample number of the first part
+        invalid[32] = 100;
+        let bank = vec![Pattern::default(), Pattern::from_bytes(&invalid)?];
+        assert_eq!(
+            syro_stream.add_pattern_bank(bank).err(),
+            Some(SyroError::InvalidPatternInBank {
+                index: 1,
+                source: Box::new(SyroError::OutOfBounds {
+                    val: 100,
+                    name: "sample_index",
+                    lo: 0,
+                    hi: 99
+                })
+            })
+        );
+        assert!(syro_stream.patterns().next().is_none());
+
+        let too_many = (0..11).map(|_| Pattern::default());
+        assert!(matches!(
+            syro_stream.add_pattern_bank(too_many),
+            Err(SyroError::InvalidPatternInBank { index: 10, .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn consuming_builder() -> anyhow::Result<()> {
+        let output = SyroStream::builder()
+            .add_sample(0, sine_wave(), 44100, None)?
+            .erase_sample(1)?
+            .add_pattern(0, Pattern::default())?
+            .build()
+            .generate()?;
+
+        let mut syro_stream = SyroStream::default();
+        syro_stream
+            .add_sample(0, sine_wave(), 44100, None)?
+            .erase_sample(1)?
+            .add_pattern(0, Pattern::default())?;
+        assert_eq!(output, syro_stream.generate()?);
+
+        assert!(SyroStream::builder().erase_sample(100).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn generate_streaming() -> anyhow::Result<()> {
+        let stream = || -> anyhow::Result<SyroStream> {
+            let mut syro_stream = SyroStream::default();
+            syro_stream.add_sample(0, sine_wave(), 44100, None)?;
+            Ok(syro_stream)
+        };
+
+        let mut written = vec![];
+        stream()?.generate_streaming(&mut written)?;
+        assert_eq!(written, stream()?.generate_wav()?);
+
+        let path = std::env::temp_dir().join("korg-syro-generate-to-path.wav");
+        stream()?.generate_to_path(&path)?;
+        assert_eq!(std::fs::read(&path)?, written);
+        std::fs::remove_file(path)?;
+
+        let result = stream()?.generate_to_path("/nonexistent/dir/out.wav");
+        assert!(matches!(result, Err(SyroError::Io { .. })));
+        Ok(())
+    }
+
+    #[test]
+    fn output_rate() -> anyhow::Result<()> {
+        let mut native = SyroStream::default();
+        native.add_sample(0, sine_wave(), 44100, None)?;
+        let native_len = native.generate()?.len() / 2;
+
+        let mut resampled = SyroStream::default();
+        resampled
+            .add_sample(0, sine_wave(), 44100, None)?
+            .output_rate(OutputRate::Hz48000)?;
+        let wav = resampled.generate_wav()?;
+        assert_eq!(LittleEndian::read_u32(&wav[24..28]), 48000);
+        let frames = (wav.len() - 44) / 4;
+        assert_eq!(
+            frames,
+            (native_len as f64 * 48000.0 / 44100.0).round() as usize
+        );
+
+        let mut streamed = vec![];
+        SyroStream::builder()
+            .add_sample(0, sine_wave(), 44100, None)?
+            .output_rate(OutputRate::Hz48000)?
+            .build()
+            .generate_streaming(&mut streamed)?;
+        assert_eq!(streamed, wav);
+
+        assert!(SyroStream::default()
+            .output_rate(OutputRate::Custom(0))
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn sample_analysis() -> anyhow::Result<()> {
+        let mut syro_stream = SyroStream::default();
+        syro_stream
+            .add_sample(0, vec![0, 100, -200, 50], 8000, None)?
+            .erase_sample(1)?;
+        assert_eq!(
+            syro_stream.sample_data(0),
+            Some((vec![0, 100, -200, 50], 8000))
+        );
+        assert_eq!(syro_stream.sample_summary(0).map(|s| s.peak), Some(200));
+        assert_eq!(
+            syro_stream.sample_peaks(0, 2),
+            Some(vec![(0, 100), (-200, 50)])
+        );
+        assert_eq!(syro_stream.sample_data(1), None);
+        assert_eq!(syro_stream.sample_summary(2), None);
+        assert_eq!(syro_stream.sample_peaks(200, 2), None);
+        Ok(())
+    }
+
+    #[test]
+    fn add_sample_auto() -> anyhow::Result<()> {
+        let mut syro_stream = SyroStream::default();
+        syro_stream
+            .add_sample(0, vec![0; 4], 44100, None)?
+            .erase_sample(1)?
+            .add_sample(3, vec![0; 4], 44100, None)?;
+        let options = AddSampleOptions::default();
+        assert_eq!(syro_stream.add_sample_auto(vec![0; 4], &options)?, 2);
+        assert_eq!(syro_stream.add_sample_auto(vec![0; 4], &options)?, 4);
+
+        for _ in 5..100 {
+            syro_stream.add_sample_auto(vec![0; 4], &options)?;
+        }
+        assert_eq!(
+            syro_stream.add_sample_auto(vec![0; 4], &options),
+            Err(SyroError::NoFreeSlot)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn slot_rejected() -> anyhow::Result<()> {
+        let out_of_range = SyroDataBundle::erase(150);
+        let result = init_syro_handle(&mut [SyroDataBundle::erase(1).data(), out_of_range.data()]);
+        assert_eq!(
+            result.err(),
+            Some(SyroError::SlotRejected {
+                slot: SlotId::Sample(150),
+                data_type: DataType::SampleErase,
+                source: Box::new(SyroError::SyroStatus {
+                    status: syro::SyroStatus::Status_OutOfRange_Number
+                }),
+            })
+        );
+
+        // caught ahead of the library
+        let mut syro_stream = SyroStream::default();
+        syro_stream.slots.insert(
+            SlotId::Sample(3),
+            SyroDataBundle::sample(
+                3,
+                syro::SyroDataType::DataType_Sample_Compress,
+                vec![0; 4],
+                44100,
+                20,
+            )?,
+        );
+        match syro_stream.generate() {
+            Err(SyroError::SlotRejected { slot, source, .. }) => {
+                assert_eq!(slot, SlotId::Sample(3));
+                assert!(matches!(*source, SyroError::OutOfBounds { .. }));
+            }
+            other => panic!("unexpected {:?}", other.map(|d| d.len())),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn gain_db() -> anyhow::Result<()> {
+        let mut plain = SyroStream::default();
+        plain.add_sample(0, sine_wave(), 44100, None)?;
+        let plain = plain.generate()?;
+
+        let mut attenuated = SyroStream::default();
+        attenuated
+            .add_sample(0, sine_wave(), 44100, None)?
+            .gain_db(-6.0)?;
+        let attenuated = attenuated.generate()?;
+        assert_eq!(attenuated.len(), plain.len());
+        let gain = 10f32.powf(-6.0 / 20.0);
+        for (a, p) in attenuated.iter().zip(plain.iter()) {
+            assert_eq!(*a, (*p as f32 * gain) as i16);
+        }
+
+        assert_eq!(
+            SyroStream::default().gain_db(1.0).err(),
+            Some(SyroError::GainOutOfRange { gain_db: 1.0 })
+        );
+        assert!(SyroStream::default().gain_db(-13.0).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn padding() -> anyhow::Result<()> {
+        let mut plain = SyroStream::default();
+        plain.add_sample(0, sine_wave(), 44100, None)?;
+        let plain = plain.generate()?;
+
+        let mut padded = SyroStream::default();
+        padded
+            .add_sample(0, sine_wave(), 44100, None)?
+            .padding(Padding {
+                lead_in: Duration::from_millis(100),
+                lead_out: Duration::from_millis(50),
+                ramp: Duration::from_millis(1),
+            });
+        let padded = padded.generate()?;
+
+        let (lead_in, lead_out) = (4410 * 2, 2205 * 2);
+        assert_eq!(padded.len(), plain.len() + lead_in + lead_out);
+        assert!(padded[..lead_in].iter().all(|&s| s == 0));
+        assert!(padded[padded.len() - lead_out..].iter().all(|&s| s == 0));
+
+        // only the 44 frame ramps at the edges differ
+        let stream = &padded[lead_in..padded.len() - lead_out];
+        let ramp = 44 * 2;
+        assert_eq!(stream[0], 0);
+        assert_eq!(
+            &stream[ramp..stream.len() - ramp],
+            &plain[ramp..plain.len() - ramp]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn calibration() -> anyhow::Result<()> {
+        let mut plain = SyroStream::default();
+        plain.add_sample(0, sine_wave(), 44100, None)?;
+        let plain = plain.generate()?;
+
+        let calibration = Calibration {
+            tone: Duration::from_millis(100),
+            level_check: Duration::from_millis(100),
+            gap: Duration::from_millis(10),
+        };
+        let mut calibrated = SyroStream::default();
+        calibrated
+            .add_sample(0, sine_wave(), 44100, None)?
+            .calibration(Some(calibration))
+            .gain_db(-6.0)?
+            .padding(Padding {
+                lead_in: Duration::from_millis(10),
+                ..Padding::default()
+            });
+        let (calibrated, report) = calibrated.generate_with_report()?;
+
+        let (tone, gap) = (4410 * 2, 441 * 2);
+        assert_eq!(calibrated.len(), plain.len() + 2 * tone + 2 * gap);
+        assert_eq!(report.items[0].offset, 4410 * 2 + 441 * 2);
+        let peak = |data: &[i16]| data.iter().map(|s| s.abs()).max().unwrap_or(0);
+        assert_eq!(peak(&calibrated[..tone]), 3276);
+        assert_eq!(peak(&calibrated[tone..2 * tone]), 16422);
+        assert_eq!(&calibrated[..4], &[0, 0, 465, 465]);
+
+        // the stream after the calibration is left as is
+        let mut padded = SyroStream::default();
+        padded
+            .add_sample(0, sine_wave(), 44100, None)?
+            .gain_db(-6.0)?
+            .padding(Padding {
+                lead_in: Duration::from_millis(10),
+                ..Padding::default()
+            });
+        assert_eq!(&calibrated[2 * tone + gap..], &padded.generate()?[..]);
+        Ok(())
+    }
+
+    #[test]
+    fn concat_streams() {
+        let streams = vec![vec![1; 4], vec![2; 6], vec![3; 2]];
+        let output = concat(&streams, Duration::from_millis(10));
+        // 441 frames of silence between each stream
+        assert_eq!(output.len(), 12 + 2 * 882);
+        assert_eq!(&output[..4], &[1; 4]);
+        assert!(output[4..886].iter().all(|&s| s == 0));
+        assert_eq!(&output[886..892], &[2; 6]);
+        assert_eq!(&output[output.len() - 2..], &[3; 2]);
+
+        assert_eq!(concat(&streams[..1], Duration::from_secs(1)), vec![1; 4]);
+        assert!(concat(&[], Duration::from_secs(1)).is_empty());
     }
 
     #[test]
@@ -443,4 +2933,140 @@ mod tests {
         let _output = syro_stream.generate()?;
         Ok(())
     }
+
+    #[test]
+    fn generator() -> anyhow::Result<()> {
+        let stream = || -> Result<SyroStream, SyroError> {
+            let mut syro_stream = SyroStream::default();
+            syro_stream.add_sample(0, sine_wave(), 44100, None)?;
+            Ok(syro_stream)
+        };
+        let expected = stream()?.generate()?;
+
+        let mut generator = stream()?.generator()?;
+        assert_eq!(generator.remaining_frames() * 2, expected.len());
+        let mut buffer = [0; 1001];
+        let mut output = vec![];
+        loop {
+            let written = generator.fill(&mut buffer)?;
+            if written == 0 {
+                break;
+            }
+            assert!(written <= 1000);
+            output.extend_from_slice(&buffer[..written]);
+        }
+        assert!(generator.is_finished());
+        assert_eq!(output, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn generate_planar() -> anyhow::Result<()> {
+        let mut syro_stream = SyroStream::default();
+        syro_stream.add_sample(0, sine_wave(), 44100, None)?;
+        let interleaved = {
+            let mut syro_stream = SyroStream::default();
+            syro_stream.add_sample(0, sine_wave(), 44100, None)?;
+            syro_stream.generate()?
+        };
+
+        let (left, right) = syro_stream.generate_planar()?;
+        assert_eq!(left.len() + right.len(), interleaved.len());
+        for (i, frame) in interleaved.chunks(2).enumerate() {
+            assert_eq!((left[i], right[i]), (frame[0], frame[1]));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn generate_wav() -> anyhow::Result<()> {
+        let stream = || -> Result<SyroStream, SyroError> {
+            let mut syro_stream = SyroStream::default();
+            syro_stream.add_sample(0, sine_wave(), 44100, None)?;
+            Ok(syro_stream)
+        };
+        let expected = stream()?.generate()?;
+        let bytes = stream()?.generate_wav()?;
+
+        let (header, data) = wav::read(&mut std::io::Cursor::new(bytes))?;
+        assert_eq!(header.channel_count, 2);
+        assert_eq!(header.sampling_rate, 44100);
+        assert_eq!(header.bits_per_sample, 16);
+        match data {
+            wav::BitDepth::Sixteen(data) => assert_eq!(data, expected),
+            _ => panic!("expected 16 bit data"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn wav_header_too_large() {
+        // the RIFF size of 36 + 2 * samples has to fit in 32 bits
+        let max = (u32::MAX as usize - 36) / 2;
+        assert!(wav_header(max, 44100).is_ok());
+        assert_eq!(
+            wav_header(max + 1, 44100),
+            Err(SyroError::WavTooLarge { samples: max + 1 })
+        );
+        assert!(wav_header(usize::MAX, 44100).is_err());
+    }
+
+    #[test]
+    fn generate_split() -> anyhow::Result<()> {
+        let mut syro_stream = SyroStream::default();
+        syro_stream
+            .add_sample(5, sine_wave(), 44100, None)?
+            .erase_sample(2)?
+            .add_pattern(3, Pattern::default())?;
+
+        let split = syro_stream.generate_split()?;
+        let slots: Vec<SlotId> = split.iter().map(|(slot, _)| *slot).collect();
+        assert_eq!(
+            slots,
+            vec![SlotId::Sample(2), SlotId::Sample(5), SlotId::Pattern(3)]
+        );
+
+        let mut single = SyroStream::default();
+        single.add_sample(5, sine_wave(), 44100, None)?;
+        assert_eq!(split[1].1, single.generate()?);
+
+        assert_eq!(
+            SyroStream::default().generate_split(),
+            Err(SyroError::EmptyStream)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn validate() -> anyhow::Result<()> {
+        let steps = Steps::from_indices(vec![0, 8])?;
+        let mut pattern = Pattern::default();
+        pattern.with_part(0, *Part::for_sample(0)?.with_steps(steps))?;
+        pattern.with_part(1, *Part::for_sample(1)?.with_steps(steps))?;
+        pattern.with_part(2, *Part::for_sample(37)?.with_steps(steps))?;
+        pattern.with_part(3, *Part::for_sample(38)?.with_steps(Steps::builder()))?;
+
+        let mut syro_stream = SyroStream::default();
+        syro_stream
+            .add_sample(0, sine_wave(), 44100, None)?
+            .erase_sample(1)?
+            .add_pattern(4, pattern)?;
+
+        assert_eq!(
+            syro_stream.validate(),
+            vec![
+                ValidationIssue::ErasedSample {
+                    pattern: 4,
+                    part: 1,
+                    sample: 1
+                },
+                ValidationIssue::MissingSample {
+                    pattern: 4,
+                    part: 2,
+                    sample: 37
+                },
+            ]
+        );
+        Ok(())
+    }
 }
@@ -6,6 +6,11 @@ macro_rules! bounds_check {
             pub (crate) const [<$i:upper _ERROR_NAME>]: &'static str = stringify! { $i };
         }
         paste! {
+            #[allow(dead_code)]
+            pub (crate) const [<$i:upper _RANGE>]: (u8, u8) = ($lo, $hi);
+        }
+        paste! {
+            #[allow(dead_code)]
             pub (crate) fn [<check_ $i>]($i: u8) -> Result<(), SyroError> {
                 if $i > $hi || $i < $lo {
                     return Err(SyroError::OutOfBounds {
@@ -27,6 +32,11 @@ macro_rules! max_check {
             pub (crate) const [<$i:upper _ERROR_NAME>]: &'static str = stringify! { $i };
         }
         paste! {
+            #[allow(dead_code)]
+            pub (crate) const [<$i:upper _RANGE>]: (u8, u8) = (0, $hi);
+        }
+        paste! {
+            #[allow(dead_code)]
             pub (crate) fn [<check_ $i>]($i: u8) -> Result<(), SyroError> {
                 if $i > $hi {
                     return Err(SyroError::OutOfBounds {
@@ -41,3 +51,47 @@ macro_rules! max_check {
         }
     };
 }
+
+// Generates a builder wrapping a type that has `&mut Self` methods, with
+// variants of those methods taking and returning the builder by value
+macro_rules! consuming_builder {
+    (
+        $(#[$m:meta])*
+        $builder:ident($inner:ident) {
+            $(fn $name:ident($($arg:ident: $ty:ty),*);)*
+        } fallible {
+            $(fn $try_name:ident($($try_arg:ident: $try_ty:ty),*);)*
+        }
+    ) => {
+        $(#[$m])*
+        pub struct $builder($inner);
+
+        impl $builder {
+            $(
+                #[doc = concat!("See [", stringify!($inner), "::", stringify!($name), "]")]
+                pub fn $name(mut self, $($arg: $ty),*) -> Self {
+                    self.0.$name($($arg),*);
+                    self
+                }
+            )*
+
+            $(
+                #[doc = concat!("See [", stringify!($inner), "::", stringify!($try_name), "]")]
+                pub fn $try_name(mut self, $($try_arg: $try_ty),*) -> Result<Self, SyroError> {
+                    self.0.$try_name($($try_arg),*)?;
+                    Ok(self)
+                }
+            )*
+
+            pub fn build(self) -> $inner {
+                self.0
+            }
+        }
+
+        impl From<$builder> for $inner {
+            fn from(builder: $builder) -> Self {
+                builder.0
+            }
+        }
+    };
+}
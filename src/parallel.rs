@@ -0,0 +1,44 @@
+//! Parallel generation of independent streams with rayon
+use rayon::prelude::*;
+
+use crate::{SyroError, SyroStream};
+
+impl SyroStream {
+    /// Generates several independent streams in parallel
+    ///
+    /// The results are returned in the same order as the streams. Each stream
+    /// is rendered by its own [SyroGenerator](crate::SyroGenerator) on a worker
+    /// thread.
+    pub fn generate_all(streams: Vec<SyroStream>) -> Vec<Result<Vec<i16>, SyroError>> {
+        streams
+            .into_par_iter()
+            .map(|stream| stream.generate())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{SyroError, SyroStream};
+
+    #[test]
+    fn generate_all() -> anyhow::Result<()> {
+        let mut streams = vec![];
+        for index in 0..4 {
+            let mut syro_stream = SyroStream::default();
+            syro_stream.add_sample(index, vec![0; 4410 * (index as usize + 1)], 44100, None)?;
+            streams.push(syro_stream);
+        }
+        streams.push(SyroStream::default());
+
+        let results = SyroStream::generate_all(streams);
+        assert_eq!(results.len(), 5);
+        for (index, result) in results.iter().take(4).enumerate() {
+            let mut syro_stream = SyroStream::default();
+            syro_stream.add_sample(index as u32, vec![0; 4410 * (index + 1)], 44100, None)?;
+            assert_eq!(result.as_ref().unwrap(), &syro_stream.generate()?);
+        }
+        assert_eq!(results[4], Err(SyroError::EmptyStream));
+        Ok(())
+    }
+}
@@ -72,6 +72,21 @@
 //! # Ok::<(), korg_syro::SyroError>(())
 //! ```
 //!
+//! Declarative, see [pattern!](crate::pattern!)
+//!
+//! ```rust
+//! use korg_syro::pattern;
+//! use korg_syro::pattern::Toggle::*;
+//!
+//! let pattern = pattern! {
+//!     0 => part(0) { steps: "x...x...x...x...", level: 100 },
+//!     1 => part(1) { steps: "....x.......x...", accents: "............x...", reverb: On },
+//! }?;
+//!
+//! # Ok::<(), korg_syro::SyroError>(())
+//! ```
+//!
+use byteorder::{ByteOrder, LittleEndian};
 use korg_syro_sys::{
     VolcaSample_Part_Data, VolcaSample_Pattern_Data, VOLCASAMPLE_FUNC_LOOP,
     VOLCASAMPLE_FUNC_MOTION, VOLCASAMPLE_FUNC_MUTE, VOLCASAMPLE_FUNC_REVERB,
@@ -79,15 +94,29 @@ use korg_syro_sys::{
     VOLCASAMPLE_PARAM_HICUT, VOLCASAMPLE_PARAM_LENGTH, VOLCASAMPLE_PARAM_LEVEL,
     VOLCASAMPLE_PARAM_PAN, VOLCASAMPLE_PARAM_PITCHEG_ATTACK, VOLCASAMPLE_PARAM_PITCHEG_DECAY,
     VOLCASAMPLE_PARAM_PITCHEG_INT, VOLCASAMPLE_PARAM_SPEED, VOLCASAMPLE_PARAM_START_POINT,
+    VOLCASAMPLE_PATTERN_DEVCODE, VOLCASAMPLE_PATTERN_FOOTER, VOLCASAMPLE_PATTERN_HEADER,
 };
 pub use num_enum;
 use num_enum::TryFromPrimitive;
+use std::convert::TryFrom;
+use std::fmt;
 
+use crate::dsp::Rng;
 use crate::macros::*;
-use crate::{check_sample_index, SyroError};
+use crate::{check_sample_index, SyroError, SAMPLE_INDEX_ERROR_NAME, SAMPLE_INDEX_RANGE};
+
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impl;
+#[cfg(feature = "midi")]
+mod midi;
+#[cfg(feature = "serde")]
+mod serde_impl;
+
+#[cfg(feature = "midi")]
+pub use midi::MidiMapping;
 
 /// Defines the available steps
-#[derive(Copy, Clone, Debug, TryFromPrimitive)]
+#[derive(Copy, Clone, Debug, PartialEq, TryFromPrimitive)]
 #[repr(u8)]
 pub enum Step {
     One,
@@ -109,39 +138,219 @@ pub enum Step {
 }
 
 impl Step {
-    pub fn to_bitmask(self) -> u16 {
+    /// Converts a 1-based step number as printed on the device, 1-16
+    pub fn from_number(number: u8) -> Result<Self, SyroError> {
+        check_step_number(number)?;
+        Self::try_from(number - 1).map_err(|_| SyroError::InvalidStep {
+            input: number.to_string(),
+        })
+    }
+
+    /// Returns the 1-based step number as printed on the device
+    pub const fn number(self) -> u8 {
+        self as u8 + 1
+    }
+
+    pub const fn to_bitmask(self) -> u16 {
         1 << self as u16
     }
 }
 
+/// Converts to the 0-based index, the inverse of `Step::try_from(u8)`
+impl From<Step> for u8 {
+    fn from(step: Step) -> Self {
+        step as u8
+    }
+}
+
+/// Parses a 1-based step number, "1" to "16"
+impl std::str::FromStr for Step {
+    type Err = SyroError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().parse::<u8>() {
+            Ok(number) => Self::from_number(number),
+            Err(_) => Err(SyroError::InvalidStep {
+                input: s.to_string(),
+            }),
+        }
+    }
+}
+
 /// Builder for a step sequence
-#[derive(Copy, Clone, Debug)]
+///
+/// The builder methods that can't fail are `const`, so fixed sequences can be
+/// defined as constants:
+///
+/// ```rust
+/// use korg_syro::pattern::{Step, Steps};
+///
+/// const FOUR_ON_THE_FLOOR: Steps = Steps::euclidean(4);
+/// const BACKBEAT: Steps = Steps::from_steps(&[Step::Five, Step::Thirteen]);
+/// const OFFBEAT: Steps = *Steps::euclidean(4).rotate_right(2);
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
 pub struct Steps {
     steps: u16,
 }
 
 impl Steps {
-    pub fn builder() -> Self {
+    pub const fn builder() -> Self {
         Self { steps: 0 }
     }
 
     /// Turns on the step
-    pub fn on(&mut self, step: Step) -> &mut Self {
+    pub const fn on(&mut self, step: Step) -> &mut Self {
         self.steps |= step.to_bitmask();
         self
     }
 
-    pub fn build(self) -> Self {
+    /// Creates steps with the given steps turned on
+    pub const fn from_steps(steps: &[Step]) -> Self {
+        let mut mask = 0;
+        let mut i = 0;
+        while i < steps.len() {
+            mask |= steps[i].to_bitmask();
+            i += 1;
+        }
+        Self { steps: mask }
+    }
+
+    /// Spreads `pulses` steps as evenly as possible over the 16 steps,
+    /// starting on [Step::One]
+    ///
+    /// More than 16 pulses turn on every step.
+    pub const fn euclidean(pulses: u8) -> Self {
+        let mut mask = 0;
+        let mut index = 0;
+        while index < 16 {
+            if (index * pulses as u32) % 16 < pulses as u32 {
+                mask |= 1 << index;
+            }
+            index += 1;
+        }
+        Self { steps: mask }
+    }
+
+    /// Turns on the step at a 0-based index, in the range 0-15
+    pub fn on_index(&mut self, index: usize) -> Result<&mut Self, SyroError> {
+        check_step_index(index.min(u8::MAX as usize) as u8)?;
+        self.steps |= 1 << index;
+        Ok(self)
+    }
+
+    /// Builds a step sequence from 0-based step indices
+    ///
+    /// Valid indices are 0-15, where 0 corresponds to [Step::One]
+    pub fn from_indices<I: IntoIterator<Item = u8>>(indices: I) -> Result<Self, SyroError> {
+        let mut steps = Self::builder();
+        for index in indices {
+            check_step_index(index)?;
+            steps.steps |= 1 << index;
+        }
+        Ok(steps)
+    }
+
+    /// Moves every step `n` steps earlier, wrapping around
+    pub const fn rotate_left(&mut self, n: u32) -> &mut Self {
+        self.steps = rotate_mask_left(self.steps, n);
+        self
+    }
+
+    /// Moves every step `n` steps later, wrapping around
+    pub const fn rotate_right(&mut self, n: u32) -> &mut Self {
+        self.steps = rotate_mask_right(self.steps, n);
+        self
+    }
+
+    /// Reverses the order of the steps
+    pub const fn reverse(&mut self) -> &mut Self {
+        self.steps = self.steps.reverse_bits();
+        self
+    }
+
+    /// Replaces the last eight steps with a mirror image of the first eight
+    pub const fn mirror(&mut self) -> &mut Self {
+        self.steps = mirror_mask(self.steps);
+        self
+    }
+
+    pub const fn build(self) -> Self {
         self
     }
 
-    pub fn to_bytes(self) -> u16 {
+    pub const fn to_bytes(self) -> u16 {
         self.steps
     }
+
+    /// Creates steps from a bitmask where bit 0 is [Step::One]
+    pub const fn from_mask(mask: u16) -> Self {
+        Self { steps: mask }
+    }
+}
+
+// Parses a step string of the pattern! macro into a bitmask, panics so that
+// an invalid literal fails to compile
+#[doc(hidden)]
+pub const fn steps_mask(steps: &str) -> u16 {
+    let bytes = steps.as_bytes();
+    let mut mask = 0;
+    let mut step = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'x' | b'X' | b'.' | b'-' => {
+                assert!(step < 16, "more than 16 steps");
+                if bytes[i] == b'x' || bytes[i] == b'X' {
+                    mask |= 1 << step;
+                }
+                step += 1;
+            }
+            b' ' | b'|' => {}
+            _ => panic!("invalid step, expected 'x' or 'X' for on, '.' or '-' for off"),
+        }
+        i += 1;
+    }
+    assert!(step == 16, "expected 16 steps");
+    mask
+}
+
+// step one is the least significant bit, so moving steps earlier is a right rotation
+const fn rotate_mask_left(mask: u16, n: u32) -> u16 {
+    mask.rotate_right(n % 16)
+}
+
+const fn rotate_mask_right(mask: u16, n: u32) -> u16 {
+    mask.rotate_left(n % 16)
+}
+
+const fn mirror_mask(mask: u16) -> u16 {
+    (mask & 0x00ff) | (mask & 0x00ff).reverse_bits()
+}
+
+fn mirror_seq(sequence: &mut [u8; 16]) {
+    for step in 0..8 {
+        sequence[15 - step] = sequence[step];
+    }
+}
+
+/// Renders the steps as a 16 column grid, `x` for on and `.` for off
+impl fmt::Display for Steps {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for index in 0..16 {
+            let c = if self.steps & (1 << index) != 0 {
+                'x'
+            } else {
+                '.'
+            };
+            write!(f, "{}", c)?;
+        }
+        Ok(())
+    }
 }
 
 /// Defines a toggle value
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum Toggle {
     On,
     Off,
@@ -149,6 +358,8 @@ pub enum Toggle {
 
 max_check!(pattern_index, 9);
 max_check!(part_index, 9);
+max_check!(step_index, 15);
+bounds_check!(step_number, 1, 16);
 
 max_check!(level, 127);
 bounds_check!(pan, 1, 127);
@@ -163,11 +374,439 @@ max_check!(starting_point, 127);
 max_check!(length, 127);
 max_check!(hi_cut, 127);
 
+max_check!(speed_continuous_value, 126);
+// a speed knob value of 0 can't be told apart from a motion step without a value
+bounds_check!(speed_motion, 1, 127);
+
 // there's two valid ranges for speed
 fn check_speed(speed: u8) -> Result<(), SyroError> {
     check_speed_semitone(speed).or(check_speed_continuous(speed))
 }
 
+// raw speed value corresponding to the original pitch
+const SPEED_SEMITONE_CENTER: i16 = 64;
+const SPEED_CONTINUOUS_OFFSET: u8 = 129;
+
+/// Defines the playback speed of a part
+///
+/// The device has two speed modes which are encoded as two disjoint raw
+/// ranges, 40-88 for semitones and 129-255 for continuous.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case", tag = "mode", content = "value")
+)]
+pub enum Speed {
+    /// Offset in semitones from the original pitch, in the range -24 to +24
+    Semitone(i8),
+    /// Continuous speed in the range 0-126
+    Continuous(u8),
+}
+
+impl Speed {
+    /// Semitone speed transposing a sample recorded at `root` to `note`
+    ///
+    /// Both are MIDI note numbers, the difference must be within two octaves.
+    pub fn from_midi_note_offset(note: u8, root: u8) -> Result<Self, SyroError> {
+        let offset = (note as i16 - root as i16).clamp(i8::MIN as i16, i8::MAX as i16);
+        let speed = Speed::Semitone(offset as i8);
+        speed.to_raw()?;
+        Ok(speed)
+    }
+
+    /// Decodes a raw speed value as stored in the pattern data
+    pub fn from_raw(raw: u8) -> Result<Self, SyroError> {
+        check_speed(raw)?;
+        if raw < SPEED_CONTINUOUS_OFFSET {
+            Ok(Speed::Semitone((raw as i16 - SPEED_SEMITONE_CENTER) as i8))
+        } else {
+            Ok(Speed::Continuous(raw - SPEED_CONTINUOUS_OFFSET))
+        }
+    }
+
+    /// Encodes the speed as a raw value for the pattern data
+    pub fn to_raw(self) -> Result<u8, SyroError> {
+        match self {
+            Speed::Semitone(offset) => {
+                // errors are reported in terms of the raw range
+                let raw = (SPEED_SEMITONE_CENTER + offset as i16).max(0) as u8;
+                check_speed_semitone(raw)?;
+                Ok(raw)
+            }
+            Speed::Continuous(value) => {
+                check_speed_continuous_value(value)?;
+                Ok(value + SPEED_CONTINUOUS_OFFSET)
+            }
+        }
+    }
+}
+
+/// Defines the parameters of a part
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Param {
+    Level,
+    Pan,
+    Speed,
+    AmpEgAttack,
+    AmpEgDecay,
+    PitchEgInt,
+    PitchEgAttack,
+    PitchEgDecay,
+    StartPoint,
+    Length,
+    HiCut,
+}
+
+impl Param {
+    /// All parameters, in the order they are stored in a part
+    pub const ALL: [Param; 11] = [
+        Param::Level,
+        Param::Pan,
+        Param::Speed,
+        Param::AmpEgAttack,
+        Param::AmpEgDecay,
+        Param::PitchEgInt,
+        Param::PitchEgAttack,
+        Param::PitchEgDecay,
+        Param::StartPoint,
+        Param::Length,
+        Param::HiCut,
+    ];
+
+    // index of the parameter in the part data
+    fn index(self) -> usize {
+        let index = match self {
+            Param::Level => VOLCASAMPLE_PARAM_LEVEL,
+            Param::Pan => VOLCASAMPLE_PARAM_PAN,
+            Param::Speed => VOLCASAMPLE_PARAM_SPEED,
+            Param::AmpEgAttack => VOLCASAMPLE_PARAM_AMPEG_ATTACK,
+            Param::AmpEgDecay => VOLCASAMPLE_PARAM_AMPEG_DECAY,
+            Param::PitchEgInt => VOLCASAMPLE_PARAM_PITCHEG_INT,
+            Param::PitchEgAttack => VOLCASAMPLE_PARAM_PITCHEG_ATTACK,
+            Param::PitchEgDecay => VOLCASAMPLE_PARAM_PITCHEG_DECAY,
+            Param::StartPoint => VOLCASAMPLE_PARAM_START_POINT,
+            Param::Length => VOLCASAMPLE_PARAM_LENGTH,
+            Param::HiCut => VOLCASAMPLE_PARAM_HICUT,
+        };
+        index as usize
+    }
+
+    // whether the raw value is at most the top of the range, in either mode
+    // for speed, the parts of a default pattern are zeroed so lower bounds are
+    // not checked
+    fn within_max(self, value: u8) -> bool {
+        match self {
+            Param::Speed => value <= SPEED_SEMITONE_RANGE.1 || value >= SPEED_CONTINUOUS_RANGE.0,
+            _ => value <= self.range().1,
+        }
+    }
+
+    // raw range of the parameter, semitone mode for speed
+    fn range(self) -> (u8, u8) {
+        match self {
+            Param::Level => LEVEL_RANGE,
+            Param::Pan => PAN_RANGE,
+            Param::Speed => SPEED_SEMITONE_RANGE,
+            Param::AmpEgAttack => AMP_EG_ATTACK_RANGE,
+            Param::AmpEgDecay => AMP_EG_DECAY_RANGE,
+            Param::PitchEgInt => PITCH_EG_INT_RANGE,
+            Param::PitchEgAttack => PITCH_EG_ATTACK_RANGE,
+            Param::PitchEgDecay => PITCH_EG_DECAY_RANGE,
+            Param::StartPoint => STARTING_POINT_RANGE,
+            Param::Length => LENGTH_RANGE,
+            Param::HiCut => HI_CUT_RANGE,
+        }
+    }
+
+    // column label in the text grid
+    fn label(self) -> &'static str {
+        match self {
+            Param::Level => "lvl",
+            Param::Pan => "pan",
+            Param::Speed => "spd",
+            Param::AmpEgAttack => "aat",
+            Param::AmpEgDecay => "adc",
+            Param::PitchEgInt => "pei",
+            Param::PitchEgAttack => "pat",
+            Param::PitchEgDecay => "pdc",
+            Param::StartPoint => "stp",
+            Param::Length => "len",
+            Param::HiCut => "hic",
+        }
+    }
+}
+
+/// Defines the parameters that can be automated with a motion sequence
+#[derive(Copy, Clone, Debug, PartialEq, TryFromPrimitive)]
+#[repr(u8)]
+pub enum Motion {
+    Level0,
+    Level1,
+    Pan0,
+    Pan1,
+    Speed0,
+    Speed1,
+    AmpEgAttack,
+    AmpEgDecay,
+    PitchEgInt,
+    PitchEgAttack,
+    PitchEgDecay,
+    StartPoint,
+    Length,
+    HiCut,
+}
+
+impl Motion {
+    /// All motion sequences, in the order they are stored in a part
+    pub const ALL: [Motion; 14] = [
+        Motion::Level0,
+        Motion::Level1,
+        Motion::Pan0,
+        Motion::Pan1,
+        Motion::Speed0,
+        Motion::Speed1,
+        Motion::AmpEgAttack,
+        Motion::AmpEgDecay,
+        Motion::PitchEgInt,
+        Motion::PitchEgAttack,
+        Motion::PitchEgDecay,
+        Motion::StartPoint,
+        Motion::Length,
+        Motion::HiCut,
+    ];
+
+    /// Returns the part parameter the motion sequence automates
+    pub fn param(self) -> Param {
+        match self {
+            Motion::Level0 | Motion::Level1 => Param::Level,
+            Motion::Pan0 | Motion::Pan1 => Param::Pan,
+            Motion::Speed0 | Motion::Speed1 => Param::Speed,
+            Motion::AmpEgAttack => Param::AmpEgAttack,
+            Motion::AmpEgDecay => Param::AmpEgDecay,
+            Motion::PitchEgInt => Param::PitchEgInt,
+            Motion::PitchEgAttack => Param::PitchEgAttack,
+            Motion::PitchEgDecay => Param::PitchEgDecay,
+            Motion::StartPoint => Param::StartPoint,
+            Motion::Length => Param::Length,
+            Motion::HiCut => Param::HiCut,
+        }
+    }
+
+    // name in the text grid
+    fn name(self) -> &'static str {
+        match self {
+            Motion::Level0 => "level0",
+            Motion::Level1 => "level1",
+            Motion::Pan0 => "pan0",
+            Motion::Pan1 => "pan1",
+            Motion::Speed0 => "speed0",
+            Motion::Speed1 => "speed1",
+            Motion::AmpEgAttack => "amp_eg_attack",
+            Motion::AmpEgDecay => "amp_eg_decay",
+            Motion::PitchEgInt => "pitch_eg_int",
+            Motion::PitchEgAttack => "pitch_eg_attack",
+            Motion::PitchEgDecay => "pitch_eg_decay",
+            Motion::StartPoint => "start_point",
+            Motion::Length => "length",
+            Motion::HiCut => "hi_cut",
+        }
+    }
+
+    // clamps a knob value to the valid range of the motion sequence
+    fn clamp(self, value: u8) -> u8 {
+        let (lo, hi) = match self {
+            Motion::Speed0 | Motion::Speed1 => SPEED_MOTION_RANGE,
+            _ => self.param().range(),
+        };
+        value.clamp(lo, hi)
+    }
+
+    // checks a knob value against the valid range of the motion sequence
+    fn check(self, value: u8) -> Result<(), SyroError> {
+        match self.param() {
+            Param::Level => check_level(value),
+            Param::Pan => check_pan(value),
+            Param::Speed => check_speed_motion(value),
+            Param::AmpEgAttack => check_amp_eg_attack(value),
+            Param::AmpEgDecay => check_amp_eg_decay(value),
+            Param::PitchEgInt => check_pitch_eg_int(value),
+            Param::PitchEgAttack => check_pitch_eg_attack(value),
+            Param::PitchEgDecay => check_pitch_eg_decay(value),
+            Param::StartPoint => check_starting_point(value),
+            Param::Length => check_length(value),
+            Param::HiCut => check_hi_cut(value),
+        }
+    }
+
+    // raw value of a knob value, the speed knob value is stored as is and
+    // all others are offset by 128
+    fn encode(self, value: u8) -> u8 {
+        match self {
+            Motion::Speed0 | Motion::Speed1 => value,
+            _ => value | MOTION_VALUE_OFFSET,
+        }
+    }
+
+    // knob value of a raw value, None for a step without a value
+    fn decode(self, raw: u8) -> Option<u8> {
+        match raw {
+            MOTION_NO_VALUE => None,
+            raw => Some(raw & !MOTION_VALUE_OFFSET),
+        }
+    }
+}
+
+/// Raw motion value marking a step where the parameter is left untouched
+pub const MOTION_NO_VALUE: u8 = 0;
+
+// offset of the raw motion values of all parameters but speed
+const MOTION_VALUE_OFFSET: u8 = 128;
+
+/// A motion sequence, holding an optional knob value per step
+///
+/// Steps without a value leave the parameter untouched on playback. The
+/// pattern data stores knob values offset by 128, apart from speed whose
+/// knob value of 1-127 is stored as is, see [MOTION_NO_VALUE].
+///
+/// Sequences built from the constructors are clamped to the valid range of the
+/// parameter they are applied to, while sequences converted from a
+/// `[u8; 16]` or `[Option<u8>; 16]` are validated and rejected if any value is
+/// out of range.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MotionSeq {
+    values: [Option<u8>; 16],
+    clamp: bool,
+}
+
+impl MotionSeq {
+    fn clamped(values: [u8; 16]) -> Self {
+        let mut sequence = Self::empty();
+        for (value, &v) in sequence.values.iter_mut().zip(values.iter()) {
+            *value = Some(v);
+        }
+        sequence
+    }
+
+    /// Sequence without any values, to be filled in with [set](MotionSeq::set)
+    pub fn empty() -> Self {
+        Self {
+            values: [None; 16],
+            clamp: true,
+        }
+    }
+
+    /// The same value on every step
+    pub fn constant(value: u8) -> Self {
+        Self::clamped([value; 16])
+    }
+
+    /// Linear ramp from the first to the last step
+    pub fn ramp(from: u8, to: u8) -> Self {
+        let mut values = [0; 16];
+        for (step, value) in values.iter_mut().enumerate() {
+            let delta = (to as f32 - from as f32) * step as f32 / 15.0;
+            *value = (from as f32 + delta).round() as u8;
+        }
+        Self::clamped(values)
+    }
+
+    /// Sine wave oscillating between `min` and `max`, `cycles` times over the 16 steps
+    pub fn sine(cycles: f32, min: u8, max: u8) -> Self {
+        let center = (min as f32 + max as f32) / 2.0;
+        let amplitude = (max as f32 - min as f32) / 2.0;
+        let mut values = [0; 16];
+        for (step, value) in values.iter_mut().enumerate() {
+            let phase = 2.0 * std::f32::consts::PI * cycles * step as f32 / 16.0;
+            *value = (center + amplitude * phase.sin()).round() as u8;
+        }
+        Self::clamped(values)
+    }
+
+    /// Evaluates the function for each 0-based step index, 0-15
+    ///
+    /// ```rust
+    /// use korg_syro::pattern::MotionSeq;
+    ///
+    /// let pulse = MotionSeq::from_fn(|step| if step % 4 == 0 { 127 } else { 40 });
+    /// ```
+    pub fn from_fn<F: FnMut(usize) -> u8>(mut f: F) -> Self {
+        let mut values = [0; 16];
+        for (step, value) in values.iter_mut().enumerate() {
+            *value = f(step);
+        }
+        Self::clamped(values)
+    }
+
+    /// Repeats the given values until all 16 steps are filled
+    ///
+    /// An empty slice results in a sequence of zeroes.
+    pub fn steps(values: &[u8]) -> Self {
+        let mut sequence = [0; 16];
+        for (value, &v) in sequence.iter_mut().zip(values.iter().cycle()) {
+            *value = v;
+        }
+        Self::clamped(sequence)
+    }
+
+    /// Sets or clears the value of a single step
+    pub fn set(&mut self, step: Step, value: Option<u8>) -> &mut Self {
+        self.values[step as usize] = value;
+        self
+    }
+
+    /// The values of the sequence, before clamping
+    pub fn values(&self) -> [Option<u8>; 16] {
+        self.values
+    }
+
+    fn resolve(self, motion: Motion) -> Result<[u8; 16], SyroError> {
+        let mut raw = [MOTION_NO_VALUE; 16];
+        for (raw, value) in raw.iter_mut().zip(self.values.iter()) {
+            if let Some(value) = *value {
+                if self.clamp {
+                    *raw = motion.encode(motion.clamp(value));
+                } else {
+                    motion.check(value)?;
+                    *raw = motion.encode(value);
+                }
+            }
+        }
+        Ok(raw)
+    }
+}
+
+impl From<[u8; 16]> for MotionSeq {
+    fn from(values: [u8; 16]) -> Self {
+        Self {
+            clamp: false,
+            ..Self::clamped(values)
+        }
+    }
+}
+
+impl From<[Option<u8>; 16]> for MotionSeq {
+    fn from(values: [Option<u8>; 16]) -> Self {
+        Self {
+            values,
+            clamp: false,
+        }
+    }
+}
+
+impl TryFrom<[Speed; 16]> for MotionSeq {
+    type Error = SyroError;
+
+    fn try_from(sequence: [Speed; 16]) -> Result<Self, Self::Error> {
+        let mut values = [0; 16];
+        for (value, speed) in values.iter_mut().zip(sequence.iter()) {
+            // the knob value, continuous speeds are offset by 128 like motion values
+            *value = speed.to_raw()? & !MOTION_VALUE_OFFSET;
+        }
+        Ok(values.into())
+    }
+}
+
 /// Defines a part of a sequence pattern
 #[derive(Copy, Clone, Debug)]
 pub struct Part {
@@ -185,20 +824,265 @@ macro_rules! impl_func_memory_part {
     };
 }
 
+macro_rules! impl_motion_seq {
+    ($(#[$m:meta])* $i:ident, $motion:ident) => {
+        $(#[$m])*
+        pub fn $i<S: Into<MotionSeq>>(&mut self, sequence: S) -> Result<&mut Self, SyroError> {
+            self.data.Motion[Motion::$motion as usize] = sequence.into().resolve(Motion::$motion)?;
+            Ok(self)
+        }
+    };
+}
+
+consuming_builder! {
+    /// Builder for a [Part] whose methods take and return it by value,
+    /// created with [Part::builder]
+    #[derive(Copy, Clone, Debug, PartialEq)]
+    PartBuilder(Part) {
+        fn init_defaults();
+        fn reset_param(param: Param);
+        fn set_normalized(param: Param, value: f32);
+        fn with_steps(steps: Steps);
+        fn with_accents(accents: Steps);
+        fn motion(value: Toggle);
+        fn looped(value: Toggle);
+        fn reverb(value: Toggle);
+        fn reverse(value: Toggle);
+        fn mute(value: Toggle);
+        fn motion_from_fn(motion: Motion, f: impl FnMut(usize) -> u8);
+        fn humanize_motion(motion: Motion, amount: u8, seed: u64);
+        fn rotate_left(n: u32);
+        fn rotate_right(n: u32);
+        fn reverse_steps();
+        fn mirror();
+    } fallible {
+        fn level(level: u8);
+        fn pan(pan: u8);
+        fn speed(speed: Speed);
+        fn speed_semitones(offset: i8);
+        fn amp_eg_attack(amp_eg_attack: u8);
+        fn amp_eg_decay(amp_eg_decay: u8);
+        fn pitch_eg_attack(pitch_eg_attack: u8);
+        fn pitch_eg_int(pitch_eg_int: u8);
+        fn pitch_eg_decay(pitch_eg_decay: u8);
+        fn starting_point(starting_point: u8);
+        fn length(length: u8);
+        fn hi_cut(hi_cut: u8);
+        fn level_start_motion_seq(sequence: impl Into<MotionSeq>);
+        fn level_end_motion_seq(sequence: impl Into<MotionSeq>);
+        fn pan_start_motion_seq(sequence: impl Into<MotionSeq>);
+        fn pan_end_motion_seq(sequence: impl Into<MotionSeq>);
+        fn speed_start_motion_seq(sequence: impl Into<MotionSeq>);
+        fn speed_end_motion_seq(sequence: impl Into<MotionSeq>);
+        fn amp_eg_attack_motion_seq(sequence: impl Into<MotionSeq>);
+        fn amp_eg_decay_motion_seq(sequence: impl Into<MotionSeq>);
+        fn pitch_eg_int_motion_seq(sequence: impl Into<MotionSeq>);
+        fn pitch_eg_attack_motion_seq(sequence: impl Into<MotionSeq>);
+        fn pitch_eg_decay_motion_seq(sequence: impl Into<MotionSeq>);
+        fn start_point_motion_seq(sequence: impl Into<MotionSeq>);
+        fn length_motion_seq(sequence: impl Into<MotionSeq>);
+        fn hi_cut_motion_seq(sequence: impl Into<MotionSeq>);
+    }
+}
+
+// Lets the pattern! macro chain both fallible and infallible builder methods
+#[doc(hidden)]
+pub trait IntoPartResult {
+    fn into_part_result(self) -> Result<PartBuilder, SyroError>;
+}
+
+impl IntoPartResult for PartBuilder {
+    fn into_part_result(self) -> Result<PartBuilder, SyroError> {
+        Ok(self)
+    }
+}
+
+impl IntoPartResult for Result<PartBuilder, SyroError> {
+    fn into_part_result(self) -> Result<PartBuilder, SyroError> {
+        self
+    }
+}
+
+/// Builds a [Pattern](crate::pattern::Pattern) from a list of parts
+///
+/// Each entry maps a part index to the sample it plays and a list of
+/// [PartBuilder](crate::pattern::PartBuilder) methods with their argument. `steps`
+/// and `accents` take a string literal of 16 steps, `x` or `X` for on and
+/// `.` or `-` for off, with spaces and `|` allowed as separators. The strings
+/// are checked at compile time, other values when the pattern is built.
+/// Evaluates to a `Result<Pattern, SyroError>`.
+///
+/// ```rust
+/// use korg_syro::pattern;
+/// use korg_syro::pattern::{MotionSeq, Toggle::*};
+///
+/// let pattern = pattern! {
+///     0 => part(0) { steps: "x... x... x... x...", level: 110 },
+///     4 => part(12) {
+///         steps: "..x. ..x. ..x. .xx.",
+///         accents: "---- ---- ---- --x-",
+///         looped: On,
+///         hi_cut_motion_seq: MotionSeq::ramp(40, 127),
+///     },
+/// }?;
+/// # Ok::<(), korg_syro::SyroError>(())
+/// ```
+///
+/// ```compile_fail
+/// let pattern = korg_syro::pattern! {
+///     0 => part(0) { steps: "x...x...x...x.." },
+/// };
+/// ```
+#[macro_export]
+macro_rules! pattern {
+    ($($index:expr => part($sample:expr) {
+        $($key:ident : $value:expr),* $(,)?
+    }),* $(,)?) => {
+        (|| -> ::std::result::Result<$crate::pattern::Pattern, $crate::SyroError> {
+            let mut pattern = $crate::pattern::Pattern::default();
+            $(
+                let builder = $crate::pattern::Part::builder($sample)?;
+                $(let builder = $crate::__pattern_part!(builder, $key, $value)?;)*
+                pattern.with_part($index, builder)?;
+            )*
+            Ok(pattern)
+        })()
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __pattern_part {
+    ($builder:ident, steps, $value:expr) => {{
+        const MASK: u16 = $crate::pattern::steps_mask($value);
+        $crate::pattern::IntoPartResult::into_part_result(
+            $builder.with_steps($crate::pattern::Steps::from_mask(MASK)),
+        )
+    }};
+    ($builder:ident, accents, $value:expr) => {{
+        const MASK: u16 = $crate::pattern::steps_mask($value);
+        $crate::pattern::IntoPartResult::into_part_result(
+            $builder.with_accents($crate::pattern::Steps::from_mask(MASK)),
+        )
+    }};
+    ($builder:ident, $key:ident, $value:expr) => {
+        $crate::pattern::IntoPartResult::into_part_result($builder.$key($value))
+    };
+}
+
+// part data as initialized by the device when a part is cleared, which is
+// what the default of the sys crate holds
+fn part_init_data() -> VolcaSample_Part_Data {
+    VolcaSample_Part_Data::default()
+}
+
 impl Part {
+    /// Creates a part for the given sample with the values of a freshly
+    /// cleared part on the device
     pub fn for_sample(sample_num: u16) -> Result<Self, SyroError> {
-        check_sample_index(sample_num as u8)?;
-        let mut data = VolcaSample_Part_Data::default();
-        data.SampleNum = sample_num;
+        check_sample_index(sample_num.min(u8::MAX as u16) as u8)?;
+        let data = VolcaSample_Part_Data {
+            SampleNum: sample_num,
+            ..part_init_data()
+        };
 
         Ok(Self { data })
     }
 
+    /// Starts a [PartBuilder] for the given sample, see [for_sample](Part::for_sample)
+    pub fn builder(sample_num: u16) -> Result<PartBuilder, SyroError> {
+        Ok(PartBuilder(Self::for_sample(sample_num)?))
+    }
+
+    /// Resets the part to the values of a freshly cleared part on the device,
+    /// keeping the sample number
+    pub fn init_defaults(&mut self) -> &mut Self {
+        let sample_num = self.data.SampleNum;
+        self.data = part_init_data();
+        self.data.SampleNum = sample_num;
+        self
+    }
+
+    /// Resets a single parameter to its value on a freshly cleared part
+    pub fn reset_param(&mut self, param: Param) -> &mut Self {
+        let defaults = part_init_data();
+        self.data.Param[param.index()] = defaults.Param[param.index()];
+        if param == Param::Level {
+            self.data.Level = defaults.Level;
+        }
+        self
+    }
+
+    /// Returns the sample the part plays
+    pub fn sample_num(&self) -> u16 {
+        self.data.SampleNum
+    }
+
+    /// Returns the active steps of the part
+    pub fn steps(&self) -> Steps {
+        Steps {
+            steps: self.data.StepOn,
+        }
+    }
+
+    /// Returns the accented steps of the part
+    pub fn accents(&self) -> Steps {
+        Steps {
+            steps: self.data.Accent,
+        }
+    }
+
+    /// Returns the raw value of a parameter
+    pub fn param(&self, param: Param) -> u8 {
+        self.data.Param[param.index()]
+    }
+
+    /// Returns the values of a motion sequence, steps without a value are None
+    pub fn motion_values(&self, motion: Motion) -> [Option<u8>; 16] {
+        let mut values = [None; 16];
+        for (value, &raw) in values
+            .iter_mut()
+            .zip(self.data.Motion[motion as usize].iter())
+        {
+            *value = motion.decode(raw);
+        }
+        values
+    }
+
+    /// Returns true if the part plays back its motion sequences
+    pub fn is_motion_enabled(&self) -> bool {
+        self.data.FuncMemoryPart & VOLCASAMPLE_FUNC_MOTION as u8 != 0
+    }
+
+    /// Returns true if the part loops its sample
+    pub fn is_looped(&self) -> bool {
+        self.data.FuncMemoryPart & VOLCASAMPLE_FUNC_LOOP as u8 != 0
+    }
+
+    /// Returns true if the part plays its sample in reverse
+    pub fn is_reversed(&self) -> bool {
+        self.data.FuncMemoryPart & VOLCASAMPLE_FUNC_REVERSE as u8 != 0
+    }
+
+    /// Returns a copy of the part playing a different sample, keeping the
+    /// steps, parameters and motion sequences
+    pub fn clone_with_sample(&self, new_sample: u16) -> Result<Self, SyroError> {
+        check_sample_index(new_sample.min(u8::MAX as u16) as u8)?;
+        let mut part = *self;
+        part.data.SampleNum = new_sample;
+        Ok(part)
+    }
+
     pub fn with_steps(&mut self, steps: Steps) -> &mut Self {
         self.data.StepOn = steps.to_bytes();
         self
     }
 
+    pub fn with_accents(&mut self, accents: Steps) -> &mut Self {
+        self.data.Accent = accents.to_bytes();
+        self
+    }
+
     fn toggle_func_memory_part(&mut self, func: u32, value: Toggle) {
         match value {
             Toggle::On => {
@@ -240,12 +1124,43 @@ impl Part {
         Ok(self)
     }
 
-    pub fn speed(&mut self, speed: u8) -> Result<&mut Self, SyroError> {
-        check_speed(speed)?;
-        self.data.Param[VOLCASAMPLE_PARAM_SPEED as usize] = speed;
+    pub fn speed(&mut self, speed: Speed) -> Result<&mut Self, SyroError> {
+        self.data.Param[VOLCASAMPLE_PARAM_SPEED as usize] = speed.to_raw()?;
         Ok(self)
     }
 
+    /// Sets a parameter from a value in the range 0.0-1.0, as produced by
+    /// knobs or MIDI CC mappings
+    ///
+    /// The value is clamped and scaled to the range of the parameter. Speed
+    /// is set in semitones, so 0.5 plays the sample at its original pitch.
+    pub fn set_normalized(&mut self, param: Param, value: f32) -> &mut Self {
+        let (lo, hi) = param.range();
+        let value = if value.is_nan() {
+            0.0
+        } else {
+            value.clamp(0.0, 1.0)
+        };
+        self.data.Param[param.index()] = lo + (value * (hi - lo) as f32).round() as u8;
+        self
+    }
+
+    /// Returns a parameter scaled to 0.0-1.0, the inverse of
+    /// [set_normalized](Part::set_normalized)
+    ///
+    /// Continuous speeds are mapped to the nearest end of the semitone range.
+    pub fn normalized(&self, param: Param) -> f32 {
+        let (lo, hi) = param.range();
+        let raw = self.param(param).clamp(lo, hi);
+        (raw - lo) as f32 / (hi - lo) as f32
+    }
+
+    /// Transposes the part by a signed number of semitones, -24 to +24,
+    /// where 0 plays the sample at its original pitch
+    pub fn speed_semitones(&mut self, offset: i8) -> Result<&mut Self, SyroError> {
+        self.speed(Speed::Semitone(offset))
+    }
+
     pub fn amp_eg_attack(&mut self, amp_eg_attack: u8) -> Result<&mut Self, SyroError> {
         check_amp_eg_attack(amp_eg_attack)?;
         self.data.Param[VOLCASAMPLE_PARAM_AMPEG_ATTACK as usize] = amp_eg_attack;
@@ -294,154 +1209,350 @@ impl Part {
         Ok(self)
     }
 
-    /// Valid values in the sequence are 0-127
-    pub fn level_start_motion_seq(&mut self, sequence: [u8; 16]) -> Result<&mut Self, SyroError> {
-        sequence
-            .iter()
-            .map(|&v| check_level(v))
-            .collect::<Result<(), SyroError>>()?;
-        self.data.Motion[korg_syro_sys::VOLCASAMPLE_MOTION_LEVEL_0 as usize] = sequence;
-        Ok(self)
+    impl_motion_seq!(
+        /// Valid values in the sequence are 0-127
+        level_start_motion_seq, Level0
+    );
+    impl_motion_seq!(
+        /// Valid values in the sequence are 0-127
+        level_end_motion_seq, Level1
+    );
+    impl_motion_seq!(
+        /// Valid values in the sequence are 1-127
+        pan_start_motion_seq, Pan0
+    );
+    impl_motion_seq!(
+        /// Valid values in the sequence are 1-127
+        pan_end_motion_seq, Pan1
+    );
+
+    impl_motion_seq!(
+        /// Valid values in the sequence are speed knob values 1-127
+        ///
+        /// Use `MotionSeq::try_from([Speed; 16])` to build a sequence from [Speed] values.
+        speed_start_motion_seq, Speed0
+    );
+    impl_motion_seq!(
+        /// Valid values in the sequence are speed knob values 1-127
+        ///
+        /// Use `MotionSeq::try_from([Speed; 16])` to build a sequence from [Speed] values.
+        speed_end_motion_seq, Speed1
+    );
+
+    impl_motion_seq!(
+        /// Valid values in the sequence are 0-127
+        amp_eg_attack_motion_seq, AmpEgAttack
+    );
+    impl_motion_seq!(
+        /// Valid values in the sequence are 0-127
+        amp_eg_decay_motion_seq, AmpEgDecay
+    );
+    impl_motion_seq!(
+        /// Valid values in the sequence are 1-127
+        pitch_eg_int_motion_seq, PitchEgInt
+    );
+    impl_motion_seq!(
+        /// Valid values in the sequence are 0-127
+        pitch_eg_attack_motion_seq, PitchEgAttack
+    );
+    impl_motion_seq!(
+        /// Valid values in the sequence are 0-127
+        pitch_eg_decay_motion_seq, PitchEgDecay
+    );
+    impl_motion_seq!(
+        /// Valid values in the sequence are 0-127
+        start_point_motion_seq, StartPoint
+    );
+    impl_motion_seq!(
+        /// Valid values in the sequence are 0-127
+        length_motion_seq, Length
+    );
+    impl_motion_seq!(
+        /// Valid values in the sequence are 0-127
+        hi_cut_motion_seq, HiCut
+    );
+
+    /// Randomly offsets the values of a motion sequence by up to `amount`,
+    /// deterministically from the given seed
+    ///
+    /// Steps without a value are offset from the current parameter value.
+    /// The results are clamped to the valid range of the parameter, and motion
+    /// playback has to be turned on with [motion](Part::motion) to be heard.
+    pub fn humanize_motion(&mut self, motion: Motion, amount: u8, seed: u64) -> &mut Self {
+        let mut rng = Rng(seed);
+        // knob value of the parameter, continuous speeds are offset by 128
+        let base = self.data.Param[motion.param().index()] & !MOTION_VALUE_OFFSET;
+        for value in self.data.Motion[motion as usize].iter_mut() {
+            let current = motion.decode(*value).unwrap_or(base);
+            let jittered = (current as i16 + rng.jitter(amount as u32) as i16).clamp(0, 255);
+            *value = motion.encode(motion.clamp(jittered as u8));
+        }
+        self
     }
 
-    /// Valid values in the sequence are 0-127
-    pub fn level_end_motion_seq(&mut self, sequence: [u8; 16]) -> Result<&mut Self, SyroError> {
-        sequence
-            .iter()
-            .map(|&v| check_level(v))
-            .collect::<Result<(), SyroError>>()?;
-        self.data.Motion[korg_syro_sys::VOLCASAMPLE_MOTION_LEVEL_1 as usize] = sequence;
-        Ok(self)
+    /// Fills a motion sequence by evaluating the function for each 0-based
+    /// step index, values are clamped to the range of the parameter
+    pub fn motion_from_fn<F: FnMut(usize) -> u8>(&mut self, motion: Motion, mut f: F) -> &mut Self {
+        for (step, value) in self.data.Motion[motion as usize].iter_mut().enumerate() {
+            *value = motion.encode(motion.clamp(f(step)));
+        }
+        self
     }
 
-    /// Valid values in the sequence are 1-127
-    pub fn pan_start_motion_seq(&mut self, sequence: [u8; 16]) -> Result<&mut Self, SyroError> {
-        sequence
-            .iter()
-            .map(|&v| check_pan(v))
-            .collect::<Result<(), SyroError>>()?;
-        self.data.Motion[korg_syro_sys::VOLCASAMPLE_MOTION_PAN_0 as usize] = sequence;
-        Ok(self)
+    fn transform<M, S>(&mut self, mask: M, sequence: S) -> &mut Self
+    where
+        M: Fn(u16) -> u16,
+        S: Fn(&mut [u8; 16]),
+    {
+        self.data.StepOn = mask(self.data.StepOn);
+        self.data.Accent = mask(self.data.Accent);
+        self.data.Motion.iter_mut().for_each(sequence);
+        self
     }
 
-    /// Valid values in the sequence are 1-127
-    pub fn pan_end_motion_seq(&mut self, sequence: [u8; 16]) -> Result<&mut Self, SyroError> {
-        sequence
-            .iter()
-            .map(|&v| check_pan(v))
-            .collect::<Result<(), SyroError>>()?;
-        self.data.Motion[korg_syro_sys::VOLCASAMPLE_MOTION_PAN_1 as usize] = sequence;
-        Ok(self)
+    /// Moves every step `n` steps earlier, wrapping around
+    ///
+    /// Steps, accents and motion sequences are moved together.
+    pub fn rotate_left(&mut self, n: u32) -> &mut Self {
+        let n = n % 16;
+        self.transform(|m| rotate_mask_left(m, n), |s| s.rotate_left(n as usize))
     }
 
-    /// Valid values in the sequence are 40-88 for semitones, and 129-255 for continuous
-    pub fn speed_start_motion_seq(&mut self, sequence: [u8; 16]) -> Result<&mut Self, SyroError> {
-        sequence
-            .iter()
-            .map(|&v| check_speed(v))
-            .collect::<Result<(), SyroError>>()?;
-        self.data.Motion[korg_syro_sys::VOLCASAMPLE_MOTION_SPEED_0 as usize] = sequence;
-        Ok(self)
+    /// Moves every step `n` steps later, wrapping around
+    ///
+    /// Steps, accents and motion sequences are moved together.
+    pub fn rotate_right(&mut self, n: u32) -> &mut Self {
+        let n = n % 16;
+        self.transform(|m| rotate_mask_right(m, n), |s| s.rotate_right(n as usize))
     }
 
-    /// Valid values in the sequence are 40-88 for semitones, and 129-255 for continuous
-    pub fn speed_end_motion_seq(&mut self, sequence: [u8; 16]) -> Result<&mut Self, SyroError> {
-        sequence
-            .iter()
-            .map(|&v| check_speed(v))
-            .collect::<Result<(), SyroError>>()?;
-        self.data.Motion[korg_syro_sys::VOLCASAMPLE_MOTION_SPEED_1 as usize] = sequence;
-        Ok(self)
+    /// Reverses the order of the steps, accents and motion sequences
+    ///
+    /// Not to be confused with [reverse](Part::reverse), which toggles reversed
+    /// playback of the sample.
+    pub fn reverse_steps(&mut self) -> &mut Self {
+        self.transform(u16::reverse_bits, |s| s.reverse())
     }
 
-    /// Valid values in the sequence are 0-127
-    pub fn amp_eg_attack_motion_seq(&mut self, sequence: [u8; 16]) -> Result<&mut Self, SyroError> {
-        sequence
-            .iter()
-            .map(|&v| check_amp_eg_attack(v))
-            .collect::<Result<(), SyroError>>()?;
-        self.data.Motion[korg_syro_sys::VOLCASAMPLE_MOTION_AMPEG_ATTACK as usize] = sequence;
-        Ok(self)
+    /// Replaces the last eight steps, accents and motion sequence values with
+    /// a mirror image of the first eight
+    pub fn mirror(&mut self) -> &mut Self {
+        self.transform(mirror_mask, mirror_seq)
     }
 
-    /// Valid values in the sequence are 0-127
-    pub fn amp_eg_decay_motion_seq(&mut self, sequence: [u8; 16]) -> Result<&mut Self, SyroError> {
-        sequence
-            .iter()
-            .map(|&v| check_amp_eg_decay(v))
-            .collect::<Result<(), SyroError>>()?;
-        self.data.Motion[korg_syro_sys::VOLCASAMPLE_MOTION_AMPEG_DECAY as usize] = sequence;
-        Ok(self)
+    pub fn build(self) -> Self {
+        self
     }
+}
 
-    /// Valid values in the sequence are 1-127
-    pub fn pitch_eg_int_motion_seq(&mut self, sequence: [u8; 16]) -> Result<&mut Self, SyroError> {
-        sequence
-            .iter()
-            .map(|&v| check_pitch_eg_int(v))
-            .collect::<Result<(), SyroError>>()?;
-        self.data.Motion[korg_syro_sys::VOLCASAMPLE_MOTION_PITCHEG_INT as usize] = sequence;
-        Ok(self)
+/// Renders the sample number followed by the step grid, accented steps are
+/// shown as `X`
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:03} ", self.data.SampleNum)?;
+        for index in 0..16 {
+            let mask = 1 << index;
+            let c = match (self.data.StepOn & mask != 0, self.data.Accent & mask != 0) {
+                (true, true) => 'X',
+                (true, false) => 'x',
+                _ => '.',
+            };
+            write!(f, "{}", c)?;
+        }
+        Ok(())
     }
+}
 
-    /// Valid values in the sequence are 0-127
-    pub fn pitch_eg_attack_motion_seq(
-        &mut self,
-        sequence: [u8; 16],
-    ) -> Result<&mut Self, SyroError> {
-        sequence
-            .iter()
-            .map(|&v| check_pitch_eg_attack(v))
-            .collect::<Result<(), SyroError>>()?;
-        self.data.Motion[korg_syro_sys::VOLCASAMPLE_MOTION_PITCHEG_ATTACK as usize] = sequence;
-        Ok(self)
+/// Size of the pattern data in bytes
+pub const PATTERN_SIZE: usize = std::mem::size_of::<VolcaSample_Pattern_Data>();
+
+/// The container fields framing the pattern data
+///
+/// The device only accepts patterns carrying the expected magic values, which
+/// makes them a quick sanity check for raw dumps, e.g. from .alldata backups.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PatternHeader {
+    pub header: u32,
+    pub dev_code: u16,
+    pub footer: u32,
+}
+
+impl PatternHeader {
+    /// Magic value at the start of the pattern data, "PTST"
+    pub const HEADER: u32 = VOLCASAMPLE_PATTERN_HEADER;
+    /// Device code of the Volca Sample
+    pub const DEV_CODE: u16 = VOLCASAMPLE_PATTERN_DEVCODE as u16;
+    /// Magic value at the end of the pattern data, "PTED"
+    pub const FOOTER: u32 = VOLCASAMPLE_PATTERN_FOOTER;
+
+    /// Reads the fields from pattern data of [PATTERN_SIZE] bytes
+    pub fn read(bytes: &[u8]) -> Result<Self, SyroError> {
+        if bytes.len() != PATTERN_SIZE {
+            return Err(SyroError::InvalidPatternSize { size: bytes.len() });
+        }
+        Ok(Self {
+            header: LittleEndian::read_u32(&bytes[..4]),
+            dev_code: LittleEndian::read_u16(&bytes[4..6]),
+            footer: LittleEndian::read_u32(&bytes[PATTERN_SIZE - 4..]),
+        })
     }
 
-    /// Valid values in the sequence are 0-127
-    pub fn pitch_eg_decay_motion_seq(
-        &mut self,
-        sequence: [u8; 16],
-    ) -> Result<&mut Self, SyroError> {
-        sequence
-            .iter()
-            .map(|&v| check_pitch_eg_decay(v))
-            .collect::<Result<(), SyroError>>()?;
-        self.data.Motion[korg_syro_sys::VOLCASAMPLE_MOTION_PITCHEG_DECAY as usize] = sequence;
-        Ok(self)
+    /// Checks the fields against the expected magic values
+    pub fn validate(&self) -> Result<(), SyroError> {
+        let fields = [
+            ("header", self.header, Self::HEADER),
+            ("device code", self.dev_code as u32, Self::DEV_CODE as u32),
+            ("footer", self.footer, Self::FOOTER),
+        ];
+        for &(field, found, expected) in fields.iter() {
+            if found != expected {
+                return Err(SyroError::InvalidPatternHeader {
+                    field,
+                    found,
+                    expected,
+                });
+            }
+        }
+        Ok(())
     }
+}
 
-    /// Valid values in the sequence are 0-127
-    pub fn start_point_motion_seq(&mut self, sequence: [u8; 16]) -> Result<&mut Self, SyroError> {
-        sequence
-            .iter()
-            .map(|&v| check_starting_point(v))
-            .collect::<Result<(), SyroError>>()?;
-        self.data.Motion[korg_syro_sys::VOLCASAMPLE_MOTION_START_POINT as usize] = sequence;
-        Ok(self)
+impl Default for PatternHeader {
+    fn default() -> Self {
+        Self {
+            header: Self::HEADER,
+            dev_code: Self::DEV_CODE,
+            footer: Self::FOOTER,
+        }
     }
+}
 
-    /// Valid values in the sequence are 0-127
-    pub fn length_motion_seq(&mut self, sequence: [u8; 16]) -> Result<&mut Self, SyroError> {
-        sequence
-            .iter()
-            .map(|&v| check_length(v))
-            .collect::<Result<(), SyroError>>()?;
-        self.data.Motion[korg_syro_sys::VOLCASAMPLE_MOTION_LENGTH as usize] = sequence;
-        Ok(self)
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn take(&mut self, len: usize) -> &'a [u8] {
+        let slice = &self.bytes[self.offset..self.offset + len];
+        self.offset += len;
+        slice
     }
 
-    /// Valid values in the sequence are 0-127
-    pub fn hi_cut_motion_seq(&mut self, sequence: [u8; 16]) -> Result<&mut Self, SyroError> {
-        sequence
-            .iter()
-            .map(|&v| check_hi_cut(v))
-            .collect::<Result<(), SyroError>>()?;
-        self.data.Motion[korg_syro_sys::VOLCASAMPLE_MOTION_HICUT as usize] = sequence;
-        Ok(self)
+    fn fill(&mut self, dest: &mut [u8]) {
+        dest.copy_from_slice(self.take(dest.len()));
     }
+}
 
-    pub fn build(self) -> Self {
-        self
+// A line of a text grid split into values, see [Pattern::from_text_grid]
+struct GridRow<'a> {
+    line: usize,
+    text: &'a str,
+    values: Vec<(usize, &'a str)>,
+    position: usize,
+}
+
+impl<'a> GridRow<'a> {
+    fn new(line: usize, text: &'a str) -> Self {
+        let mut values = vec![];
+        let mut start = None;
+        for (offset, c) in text
+            .char_indices()
+            .chain(std::iter::once((text.len(), ' ')))
+        {
+            match (start, c.is_whitespace()) {
+                (None, false) => start = Some(offset),
+                (Some(begin), true) => {
+                    values.push((begin, &text[begin..offset]));
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+        Self {
+            line,
+            text,
+            values,
+            position: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<&'a str> {
+        self.values.get(self.position).map(|&(_, value)| value)
+    }
+
+    fn error(&self, offset: usize, reason: String) -> SyroError {
+        SyroError::InvalidTextGrid {
+            line: self.line,
+            column: self.text[..offset].chars().count() + 1,
+            reason,
+        }
+    }
+
+    fn next(&mut self, expected: &str) -> Result<(usize, &'a str), SyroError> {
+        let value = self
+            .values
+            .get(self.position)
+            .copied()
+            .ok_or_else(|| self.error(self.text.len(), format!("expected {}", expected)))?;
+        self.position += 1;
+        Ok(value)
+    }
+
+    // a decimal value that `valid` accepts
+    fn value<F: Fn(u8) -> bool>(&mut self, expected: &str, valid: F) -> Result<u8, SyroError> {
+        let (offset, value) = self.next(expected)?;
+        match value.parse::<u8>() {
+            Ok(number) if valid(number) => Ok(number),
+            _ => Err(self.error(
+                offset,
+                format!("invalid {} '{}', out of range", expected, value),
+            )),
+        }
+    }
+
+    fn number(&mut self, expected: &str, radix: u32, max: u16) -> Result<u16, SyroError> {
+        let (offset, value) = self.next(expected)?;
+        match u16::from_str_radix(value, radix) {
+            Ok(number) if number <= max => Ok(number),
+            _ => Err(self.error(
+                offset,
+                format!("invalid {} '{}', expected at most {}", expected, value, max),
+            )),
+        }
+    }
+
+    // steps as (on, accent) bitmasks, `allowed` lists the valid characters
+    fn steps(&mut self, allowed: &str) -> Result<(u16, u16), SyroError> {
+        let (offset, value) = self.next("steps")?;
+        if value.chars().count() != 16 {
+            return Err(self.error(offset, format!("expected 16 steps, found '{}'", value)));
+        }
+        let (mut on, mut accent) = (0, 0);
+        for (index, (c_offset, c)) in value.char_indices().enumerate() {
+            if !allowed.contains(c) {
+                return Err(self.error(
+                    offset + c_offset,
+                    format!("invalid step '{}', expected one of '{}'", c, allowed),
+                ));
+            }
+            if c == 'x' || c == 'X' {
+                on |= 1 << index;
+            }
+            if c == 'X' || c == 'a' {
+                accent |= 1 << index;
+            }
+        }
+        Ok((on, accent))
+    }
+
+    fn end(&self) -> Result<(), SyroError> {
+        match self.values.get(self.position) {
+            Some(&(offset, value)) => {
+                Err(self.error(offset, format!("unexpected value '{}'", value)))
+            }
+            None => Ok(()),
+        }
     }
 }
 
@@ -452,12 +1563,335 @@ pub struct Pattern {
 }
 
 impl Pattern {
-    pub fn with_part(&mut self, part_index: u8, part: Part) -> Result<&Self, SyroError> {
+    /// Creates a pattern from (part index, part) pairs, parts that are not
+    /// listed keep their default values
+    pub fn from_parts<P, I>(parts: I) -> Result<Self, SyroError>
+    where
+        P: Into<Part>,
+        I: IntoIterator<Item = (u8, P)>,
+    {
+        let mut pattern = Self::default();
+        for (part_index, part) in parts {
+            pattern.with_part(part_index, part)?;
+        }
+        Ok(pattern)
+    }
+
+    /// Creates a pattern from all ten parts, None keeps the default part
+    pub fn with_parts(parts: [Option<Part>; 10]) -> Self {
+        let mut pattern = Self::default();
+        for (data, part) in pattern.data.Part.iter_mut().zip(parts.iter()) {
+            if let Some(part) = part {
+                *data = part.data;
+            }
+        }
+        pattern
+    }
+
+    pub fn with_part<P: Into<Part>>(
+        &mut self,
+        part_index: u8,
+        part: P,
+    ) -> Result<&mut Self, SyroError> {
         check_part_index(part_index)?;
+        self.data.Part[part_index as usize] = part.into().data;
+        Ok(self)
+    }
+
+    /// Copies a part from another pattern into this one
+    pub fn copy_part_from(
+        &mut self,
+        other: &Pattern,
+        src_idx: u8,
+        dst_idx: u8,
+    ) -> Result<&mut Self, SyroError> {
+        check_part_index(src_idx)?;
+        check_part_index(dst_idx)?;
+        self.data.Part[dst_idx as usize] = other.data.Part[src_idx as usize];
+        Ok(self)
+    }
+
+    /// Creates a pattern with randomly filled steps for the given parts,
+    /// deterministically from the given seed
+    ///
+    /// Each step is turned on with a probability of `density`, which ranges
+    /// from 0.0 to 1.0. Parts that are not listed keep their default values.
+    pub fn randomize<I: IntoIterator<Item = u8>>(
+        rng_seed: u64,
+        density: f32,
+        parts: I,
+    ) -> Result<Self, SyroError> {
+        let mut rng = Rng(rng_seed);
+        let mut pattern = Self::default();
+        for part_index in parts {
+            check_part_index(part_index)?;
+            let mut steps = 0;
+            for step in 0..16 {
+                if rng.next_f32() < density {
+                    steps |= 1 << step;
+                }
+            }
+            pattern.data.Part[part_index as usize].StepOn = steps;
+        }
+        Ok(pattern)
+    }
+
+    fn for_each_part<F: Fn(&mut Part)>(&mut self, f: F) -> &mut Self {
+        for data in self.data.Part.iter_mut() {
+            let mut part = Part { data: *data };
+            f(&mut part);
+            *data = part.data;
+        }
+        self
+    }
+
+    /// Moves every step of every part `n` steps earlier, wrapping around
+    pub fn rotate_left(&mut self, n: u32) -> &mut Self {
+        self.for_each_part(|part| {
+            part.rotate_left(n);
+        })
+    }
+
+    /// Moves every step of every part `n` steps later, wrapping around
+    pub fn rotate_right(&mut self, n: u32) -> &mut Self {
+        self.for_each_part(|part| {
+            part.rotate_right(n);
+        })
+    }
+
+    /// Reverses the order of the steps of every part
+    pub fn reverse(&mut self) -> &mut Self {
+        self.for_each_part(|part| {
+            part.reverse_steps();
+        })
+    }
+
+    /// Replaces the last eight steps of every part with a mirror image of the first eight
+    pub fn mirror(&mut self) -> &mut Self {
+        self.for_each_part(|part| {
+            part.mirror();
+        })
+    }
+
+    /// Moves the steps of a single part by `n` steps, later for positive
+    /// values and earlier for negative values, wrapping around
+    pub fn shift_part(&mut self, part_index: u8, n: i8) -> Result<&mut Self, SyroError> {
+        check_part_index(part_index)?;
+        let mut part = Part {
+            data: self.data.Part[part_index as usize],
+        };
+        if n < 0 {
+            part.rotate_left(n.unsigned_abs() as u32);
+        } else {
+            part.rotate_right(n as u32);
+        }
         self.data.Part[part_index as usize] = part.data;
         Ok(self)
     }
 
+    /// Returns the container fields of the pattern
+    pub fn header(&self) -> PatternHeader {
+        PatternHeader {
+            header: self.data.Header,
+            dev_code: self.data.DevCode,
+            footer: self.data.Footer,
+        }
+    }
+
+    /// Returns the steps played by the pattern, inactive steps are skipped
+    pub fn active_steps(&self) -> Steps {
+        Steps {
+            steps: self.data.ActiveStep,
+        }
+    }
+
+    /// Iterates over copies of the ten parts of the pattern
+    pub fn parts(&self) -> impl Iterator<Item = Part> + '_ {
+        self.data.Part.iter().map(|&data| Part { data })
+    }
+
+    /// Reports which parts, parameters, steps and motion sequences differ
+    /// from the other pattern
+    pub fn diff(&self, other: &Pattern) -> PatternDiff {
+        PatternDiff {
+            active_steps: self.data.ActiveStep != other.data.ActiveStep,
+            parts: self
+                .data
+                .Part
+                .iter()
+                .zip(other.data.Part.iter())
+                .enumerate()
+                .map(|(index, (a, b))| PartDiff::new(index as u8, a, b))
+                .filter(|part| !part.is_empty())
+                .collect(),
+        }
+    }
+
+    /// Renders the whole pattern as fixed-width text
+    ///
+    /// Unlike [Display](fmt::Display) the grid holds everything that is
+    /// stored for the pattern: the active steps, then a row per part with the
+    /// sample, steps, part volume, parameters and the hex function flags,
+    /// then a row per motion sequence that holds any values. Steps are `X`
+    /// when accented, `x` when on, `a` when only accented and `.` otherwise.
+    /// Steps without a motion value are `.`.
+    pub fn to_text_grid(&self) -> String {
+        let mut grid = format!("active {}\n", self.active_steps());
+        grid.push_str("part smp steps            vol");
+        for param in Param::ALL.iter() {
+            grid.push_str(&format!(" {}", param.label()));
+        }
+        grid.push_str(" fn\n");
+        for (index, part) in self.data.Part.iter().enumerate() {
+            grid.push_str(&format!("{:>4} {:03} ", index, part.SampleNum));
+            for step in 0..16 {
+                let mask = 1 << step;
+                grid.push(match (part.StepOn & mask != 0, part.Accent & mask != 0) {
+                    (true, true) => 'X',
+                    (true, false) => 'x',
+                    (false, true) => 'a',
+                    (false, false) => '.',
+                });
+            }
+            grid.push_str(&format!(" {:>3}", part.Level));
+            for param in Param::ALL.iter() {
+                grid.push_str(&format!(" {:>3}", part.Param[param.index()]));
+            }
+            grid.push_str(&format!(" {:02x}\n", part.FuncMemoryPart));
+        }
+
+        grid.push_str("part motion         ");
+        for step in 1..=16 {
+            grid.push_str(&format!(" {:>3}", step));
+        }
+        grid.push('\n');
+        for (index, part) in self.data.Part.iter().enumerate() {
+            for motion in Motion::ALL.iter() {
+                let values = &part.Motion[*motion as usize];
+                if values.iter().all(|&value| value == MOTION_NO_VALUE) {
+                    continue;
+                }
+                grid.push_str(&format!("{:>4} {:<15}", index, motion.name()));
+                for &value in values.iter() {
+                    match motion.decode(value) {
+                        None => grid.push_str("   ."),
+                        Some(value) => grid.push_str(&format!(" {:>3}", value)),
+                    }
+                }
+                grid.push('\n');
+            }
+        }
+        grid
+    }
+
+    /// Parses a pattern in the format produced by [to_text_grid](Pattern::to_text_grid)
+    ///
+    /// Blank lines and header rows are skipped, parts without a row keep the
+    /// values of a default pattern. Errors carry the line and column of the
+    /// offending value.
+    pub fn from_text_grid(grid: &str) -> Result<Self, SyroError> {
+        let mut pattern = Pattern::default();
+        for (index, text) in grid.lines().enumerate() {
+            let mut row = GridRow::new(index + 1, text);
+            match row.peek() {
+                None | Some("part") => continue,
+                Some("active") => {
+                    row.next("active")?;
+                    pattern.data.ActiveStep = row.steps("x.")?.0;
+                }
+                Some(_) => {
+                    let part_index = row.number("part index", 10, 9)? as usize;
+                    let part = &mut pattern.data.Part[part_index];
+                    match row.peek() {
+                        Some(value) if value.starts_with(|c: char| c.is_ascii_digit()) => {
+                            part.SampleNum =
+                                row.number("sample number", 10, SAMPLE_INDEX_RANGE.1 as u16)?;
+                            let (on, accent) = row.steps("Xxa.")?;
+                            part.StepOn = on;
+                            part.Accent = accent;
+                            part.Level = row.value("volume", |value| check_level(value).is_ok())?;
+                            for param in Param::ALL.iter() {
+                                part.Param[param.index()] =
+                                    row.value(param.label(), |value| param.within_max(value))?;
+                            }
+                            part.FuncMemoryPart = row.number("function flags", 16, 255)? as u8;
+                        }
+                        _ => {
+                            let (offset, name) = row.next("motion or sample number")?;
+                            let motion = Motion::ALL
+                                .iter()
+                                .copied()
+                                .find(|motion| motion.name() == name)
+                                .ok_or_else(|| {
+                                    row.error(offset, format!("unknown motion '{}'", name))
+                                })?;
+                            for step in 0..16 {
+                                part.Motion[motion as usize][step] = match row.peek() {
+                                    Some(".") => {
+                                        row.next("motion value")?;
+                                        MOTION_NO_VALUE
+                                    }
+                                    _ => motion.encode(row.value("motion value", |value| {
+                                        motion.check(value).is_ok()
+                                    })?),
+                                };
+                            }
+                        }
+                    }
+                }
+            }
+            row.end()?;
+        }
+        Ok(pattern)
+    }
+
+    /// Checks that every part plays a sample in the range 0-99
+    pub fn validate(&self) -> Result<(), SyroError> {
+        for part in self.data.Part.iter() {
+            if part.SampleNum > SAMPLE_INDEX_RANGE.1 as u16 {
+                return Err(SyroError::OutOfBounds {
+                    val: part.SampleNum as u32,
+                    name: SAMPLE_INDEX_ERROR_NAME,
+                    lo: SAMPLE_INDEX_RANGE.0 as usize,
+                    hi: SAMPLE_INDEX_RANGE.1 as usize,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Parses pattern data in the format produced by [to_bytes](Pattern::to_bytes)
+    ///
+    /// Fails on data without the magic values of a [PatternHeader].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SyroError> {
+        PatternHeader::read(bytes)?.validate()?;
+        let mut reader = ByteReader { bytes, offset: 0 };
+        let mut data = VolcaSample_Pattern_Data {
+            Header: LittleEndian::read_u32(reader.take(4)),
+            DevCode: LittleEndian::read_u16(reader.take(2)),
+            ..Default::default()
+        };
+        reader.fill(&mut data.Reserved);
+        data.ActiveStep = LittleEndian::read_u16(reader.take(2));
+        reader.fill(&mut data.Padding1);
+        for part in data.Part.iter_mut() {
+            part.SampleNum = LittleEndian::read_u16(reader.take(2));
+            part.StepOn = LittleEndian::read_u16(reader.take(2));
+            part.Accent = LittleEndian::read_u16(reader.take(2));
+            part.Reserved = LittleEndian::read_u16(reader.take(2));
+            part.Level = reader.take(1)[0];
+            reader.fill(&mut part.Param);
+            part.FuncMemoryPart = reader.take(1)[0];
+            reader.fill(&mut part.Padding1);
+            for motion in part.Motion.iter_mut() {
+                reader.fill(motion);
+            }
+        }
+        reader.fill(&mut data.Padding2);
+        data.Footer = LittleEndian::read_u32(reader.take(4));
+        Ok(Self { data })
+    }
+
     pub fn to_bytes(self) -> Vec<u8> {
         let mut bytes = vec![];
         bytes.extend_from_slice(&self.data.Header.to_le_bytes());
@@ -466,19 +1900,7 @@ impl Pattern {
         bytes.extend_from_slice(&self.data.ActiveStep.to_le_bytes());
         bytes.extend_from_slice(&self.data.Padding1);
         for part in self.data.Part.iter() {
-            let mut part_bytes = vec![];
-            part_bytes.extend_from_slice(&part.SampleNum.to_le_bytes());
-            part_bytes.extend_from_slice(&part.StepOn.to_le_bytes());
-            part_bytes.extend_from_slice(&part.Accent.to_le_bytes());
-            part_bytes.extend_from_slice(&part.Reserved.to_le_bytes());
-            part_bytes.extend_from_slice(&part.Level.to_le_bytes());
-            part_bytes.extend_from_slice(&part.Param);
-            part_bytes.extend_from_slice(&part.FuncMemoryPart.to_le_bytes());
-            part_bytes.extend_from_slice(&part.Padding1);
-            for motion in part.Motion.iter() {
-                part_bytes.extend_from_slice(motion);
-            }
-            bytes.extend_from_slice(part_bytes.as_slice());
+            write_part(part, &mut bytes);
         }
         bytes.extend_from_slice(&self.data.Padding2);
         bytes.extend_from_slice(&self.data.Footer.to_le_bytes());
@@ -486,6 +1908,117 @@ impl Pattern {
     }
 }
 
+fn write_part(part: &VolcaSample_Part_Data, bytes: &mut Vec<u8>) {
+    bytes.extend_from_slice(&part.SampleNum.to_le_bytes());
+    bytes.extend_from_slice(&part.StepOn.to_le_bytes());
+    bytes.extend_from_slice(&part.Accent.to_le_bytes());
+    bytes.extend_from_slice(&part.Reserved.to_le_bytes());
+    bytes.extend_from_slice(&part.Level.to_le_bytes());
+    bytes.extend_from_slice(&part.Param);
+    bytes.extend_from_slice(&part.FuncMemoryPart.to_le_bytes());
+    bytes.extend_from_slice(&part.Padding1);
+    for motion in part.Motion.iter() {
+        bytes.extend_from_slice(motion);
+    }
+}
+
+impl PartialEq for Part {
+    fn eq(&self, other: &Self) -> bool {
+        let (mut a, mut b) = (vec![], vec![]);
+        write_part(&self.data, &mut a);
+        write_part(&other.data, &mut b);
+        a == b
+    }
+}
+
+impl PartialEq for Pattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.clone().to_bytes() == other.clone().to_bytes()
+    }
+}
+
+/// Differences between two patterns, see [Pattern::diff]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PatternDiff {
+    /// Whether the active steps differ
+    pub active_steps: bool,
+    /// The differing parts, in part order
+    pub parts: Vec<PartDiff>,
+}
+
+impl PatternDiff {
+    /// Returns true if the patterns are the same
+    pub fn is_empty(&self) -> bool {
+        !self.active_steps && self.parts.is_empty()
+    }
+}
+
+/// Differences between two parts, see [Pattern::diff]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PartDiff {
+    /// The part index, 0-9
+    pub index: u8,
+    /// Whether the parts play different samples
+    pub sample: bool,
+    /// The steps that are on in only one of the parts
+    pub steps: Steps,
+    /// The steps that are accented in only one of the parts
+    pub accents: Steps,
+    /// Whether the motion, loop, reverb, reverse or mute switches differ
+    pub toggles: bool,
+    pub params: Vec<Param>,
+    pub motions: Vec<Motion>,
+}
+
+impl PartDiff {
+    fn new(index: u8, a: &VolcaSample_Part_Data, b: &VolcaSample_Part_Data) -> Self {
+        PartDiff {
+            index,
+            sample: a.SampleNum != b.SampleNum,
+            steps: Steps {
+                steps: a.StepOn ^ b.StepOn,
+            },
+            accents: Steps {
+                steps: a.Accent ^ b.Accent,
+            },
+            toggles: a.FuncMemoryPart != b.FuncMemoryPart,
+            params: Param::ALL
+                .iter()
+                .copied()
+                .filter(|param| {
+                    a.Param[param.index()] != b.Param[param.index()]
+                        || (*param == Param::Level && a.Level != b.Level)
+                })
+                .collect(),
+            motions: Motion::ALL
+                .iter()
+                .copied()
+                .filter(|&motion| a.Motion[motion as usize] != b.Motion[motion as usize])
+                .collect(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        !self.sample
+            && self.steps.steps == 0
+            && self.accents.steps == 0
+            && !self.toggles
+            && self.params.is_empty()
+            && self.motions.is_empty()
+    }
+}
+
+/// Renders all ten parts as rows of a step grid, prefixed with the part index
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "       1   5   9   13")?;
+        for (index, part) in self.parts().enumerate() {
+            write!(f, "\n{:>2} {}", index, part)?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::Toggle::*;
@@ -507,17 +2040,293 @@ mod test {
         assert_eq!(steps, 0b000100001000100);
     }
 
+    #[test]
+    fn test_steps_from_indices() -> anyhow::Result<()> {
+        let steps = Steps::from_indices(vec![2, 6, 11])?.to_bytes();
+        assert_eq!(steps, 0b000100001000100);
+
+        let result = Steps::from_indices(0..=16);
+        assert_eq!(
+            result.err().unwrap(),
+            SyroError::OutOfBounds {
+                val: 16,
+                name: "step_index",
+                lo: 0,
+                hi: 15
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_const_steps() -> anyhow::Result<()> {
+        const KICK: Steps = Steps::euclidean(4);
+        const SNARE: Steps = *Steps::builder().on(Step::Five).on(Step::Thirteen);
+        const HATS: Steps = *Steps::euclidean(4).rotate_right(2);
+        assert_eq!(KICK, Steps::from_indices(vec![0, 4, 8, 12])?);
+        assert_eq!(SNARE, Steps::from_steps(&[Step::Five, Step::Thirteen]));
+        assert_eq!(HATS, Steps::from_indices(vec![2, 6, 10, 14])?);
+        assert_eq!(Steps::euclidean(3).to_string(), "x.....x....x....");
+        assert_eq!(Steps::euclidean(0).to_bytes(), 0);
+        assert_eq!(Steps::euclidean(20).to_bytes(), 0xffff);
+        Ok(())
+    }
+
+    #[test]
+    fn test_steps_transform() -> anyhow::Result<()> {
+        let steps = Steps::from_indices(vec![0, 1, 5])?;
+        assert_eq!(steps.clone().rotate_left(1).to_string(), "x...x..........x");
+        assert_eq!(
+            steps.clone().rotate_right(17).to_string(),
+            ".xx...x........."
+        );
+        assert_eq!(steps.clone().reverse().to_string(), "..........x...xx");
+        assert_eq!(steps.clone().mirror().to_string(), "xx...x....x...xx");
+        Ok(())
+    }
+
+    #[test]
+    fn test_pattern_transform() -> anyhow::Result<()> {
+        let part = Part::for_sample(1)?
+            .with_steps(Steps::from_indices(vec![0, 4])?)
+            .with_accents(Steps::from_indices(vec![4])?)
+            .level_start_motion_seq(MotionSeq::ramp(0, 15))?
+            .build();
+        let mut pattern = Pattern::default();
+        pattern.with_part(0, part)?;
+        pattern.with_part(1, part)?;
+
+        pattern.shift_part(1, -2)?;
+        let parts: Vec<Part> = pattern.parts().collect();
+        assert_eq!(parts[0].to_string(), "001 x...X...........");
+        assert_eq!(parts[1].to_string(), "001 ..X...........x.");
+        assert_eq!(
+            parts[1].motion_values(Motion::Level0)[..3],
+            [Some(2), Some(3), Some(4)]
+        );
+
+        pattern.reverse();
+        let parts: Vec<Part> = pattern.parts().collect();
+        assert_eq!(parts[0].to_string(), "001 ...........X...x");
+        assert_eq!(parts[0].motion_values(Motion::Level0)[0], Some(15));
+
+        assert!(pattern.shift_part(10, 1).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_copy_part() -> anyhow::Result<()> {
+        let mut part = Part::for_sample(3)?;
+        part.with_steps(Steps::from_indices(vec![0, 4, 8, 12])?)
+            .level(100)?;
+        let mut groove = Pattern::default();
+        groove.with_part(0, part)?;
+
+        let other = part.clone_with_sample(42)?;
+        assert_eq!(other.data.SampleNum, 42);
+        assert_eq!(other.data.StepOn, part.data.StepOn);
+        assert_eq!(other.data.Param, part.data.Param);
+        assert!(part.clone_with_sample(100).is_err());
+
+        let mut pattern = Pattern::default();
+        pattern.copy_part_from(&groove, 0, 5)?;
+        let copied = pattern.parts().nth(5).unwrap();
+        assert_eq!(copied.data.SampleNum, 3);
+        assert_eq!(copied.data.StepOn, part.data.StepOn);
+        assert!(pattern.copy_part_from(&groove, 10, 0).is_err());
+        assert!(pattern.copy_part_from(&groove, 0, 10).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_part_consuming_builder() -> anyhow::Result<()> {
+        let mut pattern = Pattern::default();
+        pattern.with_part(
+            1,
+            Part::builder(5)?
+                .with_steps(Steps::from_indices(vec![0, 8])?)
+                .level(100)?
+                .reverb(Toggle::On)
+                .pan_start_motion_seq(MotionSeq::ramp(1, 127))?,
+        )?;
+
+        let mut expected = Part::for_sample(5)?;
+        expected
+            .with_steps(Steps::from_indices(vec![0, 8])?)
+            .level(100)?
+            .reverb(Toggle::On)
+            .pan_start_motion_seq(MotionSeq::ramp(1, 127))?;
+        assert_eq!(pattern.parts().nth(1), Some(expected));
+        assert!(Part::builder(5)?.pan(0).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_steps_on_index() -> anyhow::Result<()> {
+        let mut steps = Steps::builder();
+        for i in (0..16).step_by(4) {
+            steps.on_index(i)?;
+        }
+        assert_eq!(steps, Steps::from_indices(vec![0, 4, 8, 12])?);
+        assert!(steps.on_index(16).is_err());
+        assert!(steps.on_index(usize::MAX).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_step_conversions() -> anyhow::Result<()> {
+        assert_eq!(Step::from_number(1)?, Step::One);
+        assert_eq!(Step::from_number(16)?, Step::Sixteen);
+        assert!(Step::from_number(0).is_err());
+        assert!(Step::from_number(17).is_err());
+        assert_eq!(Step::Twelve.number(), 12);
+
+        assert_eq!(" 5".parse::<Step>()?, Step::Five);
+        assert!("0".parse::<Step>().is_err());
+        assert_eq!(
+            "x".parse::<Step>(),
+            Err(SyroError::InvalidStep {
+                input: "x".to_string()
+            })
+        );
+
+        assert_eq!(u8::from(Step::Three), 2);
+        assert_eq!(Step::try_from(u8::from(Step::Nine))?, Step::Nine);
+        Ok(())
+    }
+
+    #[test]
+    fn test_part_defaults() -> anyhow::Result<()> {
+        let pattern = Pattern::default();
+        let cleared = pattern.parts().next().unwrap();
+
+        let mut part = Part::for_sample(cleared.sample_num())?;
+        part.with_steps(Steps::from_indices(vec![3])?);
+        part.init_defaults();
+        assert_eq!(part, cleared);
+
+        part.pan(10)?.level(20)?.hi_cut(30)?;
+        part.reset_param(Param::Pan).reset_param(Param::Level);
+        assert_eq!(
+            part.data.Param[Param::Pan.index()],
+            cleared.data.Param[Param::Pan.index()]
+        );
+        assert_eq!(
+            part.data.Param[Param::Level.index()],
+            cleared.data.Param[Param::Level.index()]
+        );
+        assert_eq!(part.data.Param[Param::HiCut.index()], 30);
+
+        assert_eq!(Part::for_sample(7)?.init_defaults().sample_num(), 7);
+        Ok(())
+    }
+
+    #[test]
+    fn test_pattern_diff() -> anyhow::Result<()> {
+        let mut part = Part::for_sample(1)?;
+        part.with_steps(Steps::from_indices(vec![0, 4])?);
+        let mut a = Pattern::default();
+        a.with_part(2, part)?;
+        let mut b = a.clone();
+        assert_eq!(a, b);
+        assert!(a.diff(&b).is_empty());
+
+        part.with_steps(Steps::from_indices(vec![0, 8])?)
+            .pan(10)?
+            .reverb(Toggle::On)
+            .hi_cut_motion_seq(MotionSeq::constant(5))?;
+        b.with_part(2, part)?;
+        assert_ne!(a, b);
+        assert_eq!(
+            a.diff(&b),
+            PatternDiff {
+                active_steps: false,
+                parts: vec![PartDiff {
+                    index: 2,
+                    sample: false,
+                    steps: Steps::from_indices(vec![4, 8])?,
+                    accents: Steps::default(),
+                    toggles: true,
+                    params: vec![Param::Pan],
+                    motions: vec![Motion::HiCut],
+                }],
+            }
+        );
+        assert_eq!(Part::for_sample(1)?, Part::for_sample(1)?);
+        assert_ne!(Part::for_sample(1)?, Part::for_sample(2)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_randomize() -> anyhow::Result<()> {
+        let pattern = Pattern::randomize(42, 0.5, vec![0, 3])?;
+        assert_eq!(
+            pattern.to_string(),
+            Pattern::randomize(42, 0.5, vec![0, 3])?.to_string()
+        );
+        assert_ne!(
+            pattern.to_string(),
+            Pattern::randomize(43, 0.5, vec![0, 3])?.to_string()
+        );
+        let parts: Vec<Part> = pattern.parts().collect();
+        assert_ne!(parts[0].data.StepOn, 0);
+        assert_eq!(parts[1].data.StepOn, 0);
+
+        let full = Pattern::randomize(1, 1.0, vec![9])?;
+        assert_eq!(full.parts().nth(9).unwrap().data.StepOn, 0xffff);
+        assert!(Pattern::randomize(1, 0.5, vec![10]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_humanize_motion() -> anyhow::Result<()> {
+        let mut part = Part::for_sample(0)?.pan(64)?.build();
+        part.humanize_motion(Motion::Pan0, 10, 7);
+        let pan = part.motion_values(Motion::Pan0);
+        assert!(pan.iter().all(|v| matches!(v, Some(54..=74))));
+        assert!(pan.iter().any(|&v| v != Some(64)));
+
+        let mut part = Part::for_sample(0)?
+            .pan_start_motion_seq(MotionSeq::constant(1))?
+            .build();
+        part.humanize_motion(Motion::Pan0, 127, 7);
+        let pan = part.motion_values(Motion::Pan0);
+        assert!(pan.iter().all(|v| matches!(v, Some(1..=127))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_display() -> anyhow::Result<()> {
+        let steps = Steps::from_indices(vec![0, 4, 8, 12])?;
+        assert_eq!(steps.to_string(), "x...x...x...x...");
+
+        let part = Part::for_sample(42)?
+            .with_steps(steps)
+            .with_accents(Steps::from_indices(vec![4])?)
+            .build();
+        assert_eq!(part.to_string(), "042 x...X...x...x...");
+
+        let mut pattern = Pattern::default();
+        pattern.with_part(1, part)?;
+        let grid = pattern.to_string();
+        let lines: Vec<&str> = grid.lines().collect();
+        assert_eq!(lines.len(), 11);
+        assert_eq!(lines[0], "       1   5   9   13");
+        assert_eq!(lines[2], " 1 042 x...X...x...x...");
+        Ok(())
+    }
+
     #[test]
     fn test_part_builder() -> anyhow::Result<()> {
         let motion_seq: [u8; 16] = [
             1, 8, 16, 24, 32, 40, 48, 56, 64, 72, 80, 88, 96, 104, 112, 120,
         ];
-        let continuous_speed_motion_seq: [u8; 16] = [
-            129, 137, 145, 153, 161, 169, 177, 185, 193, 201, 209, 217, 225, 233, 241, 249,
-        ];
-        let semitone_speed_motion_seq: [u8; 16] = [
-            40, 43, 46, 49, 52, 55, 58, 61, 64, 67, 70, 73, 76, 79, 82, 85,
-        ];
+        let mut continuous_speed_motion_seq = [Speed::Continuous(0); 16];
+        let mut semitone_speed_motion_seq = [Speed::Semitone(0); 16];
+        for i in 0..16 {
+            continuous_speed_motion_seq[i] = Speed::Continuous(i as u8 * 8);
+            semitone_speed_motion_seq[i] = Speed::Semitone(i as i8 * 3 - 24);
+        }
         let _part = Part::for_sample(0)?
             .with_steps(
                 Steps::builder()
@@ -536,12 +2345,13 @@ mod test {
             .starting_point(42)?
             .length(42)?
             .hi_cut(42)?
+            .speed(Speed::Semitone(-12))?
             .level_start_motion_seq(motion_seq.clone())?
             .level_end_motion_seq(motion_seq.clone())?
             .pan_start_motion_seq(motion_seq.clone())?
             .pan_end_motion_seq(motion_seq.clone())?
-            .speed_start_motion_seq(continuous_speed_motion_seq.clone())?
-            .speed_end_motion_seq(semitone_speed_motion_seq.clone())?
+            .speed_start_motion_seq(MotionSeq::try_from(continuous_speed_motion_seq)?)?
+            .speed_end_motion_seq(MotionSeq::try_from(semitone_speed_motion_seq)?)?
             .amp_eg_attack_motion_seq(motion_seq.clone())?
             .amp_eg_decay_motion_seq(motion_seq.clone())?
             .pitch_eg_int_motion_seq(motion_seq.clone())?
@@ -560,6 +2370,157 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_speed() -> anyhow::Result<()> {
+        assert_eq!(Speed::Semitone(-24).to_raw()?, 40);
+        assert_eq!(Speed::Semitone(0).to_raw()?, 64);
+        assert_eq!(Speed::Semitone(24).to_raw()?, 88);
+        assert_eq!(Speed::Continuous(0).to_raw()?, 129);
+        assert_eq!(Speed::Continuous(126).to_raw()?, 255);
+        assert!(Speed::Semitone(25).to_raw().is_err());
+        assert!(Speed::Semitone(-128).to_raw().is_err());
+        assert!(Speed::Continuous(127).to_raw().is_err());
+
+        assert_eq!(Speed::from_raw(52)?, Speed::Semitone(-12));
+        assert_eq!(Speed::from_raw(200)?, Speed::Continuous(71));
+        assert!(Speed::from_raw(100).is_err());
+
+        assert_eq!(Speed::from_midi_note_offset(67, 60)?, Speed::Semitone(7));
+        assert!(Speed::from_midi_note_offset(127, 0).is_err());
+
+        let mut part = Part::for_sample(0)?;
+        assert_eq!(part.speed_semitones(-12)?.param(Param::Speed), 52);
+        assert_eq!(part.speed_semitones(24)?.param(Param::Speed), 88);
+        assert!(part.speed_semitones(25).is_err());
+        assert_eq!(part.param(Param::Speed), 88);
+        Ok(())
+    }
+
+    #[test]
+    fn test_motion_from_fn() -> anyhow::Result<()> {
+        let sequence = MotionSeq::from_fn(|step| step as u8 * 10);
+        assert_eq!(sequence.values()[15], Some(150));
+
+        let part = Part::builder(0)?
+            .hi_cut_motion_seq(sequence)?
+            .motion_from_fn(Motion::Pan0, |step| step as u8 * 10)
+            .build();
+        assert_eq!(part.motion_values(Motion::HiCut)[15], Some(127));
+        let pan = part.motion_values(Motion::Pan0);
+        assert_eq!(pan[0], Some(1));
+        assert_eq!(pan[3], Some(30));
+        assert_eq!(pan[15], Some(127));
+        Ok(())
+    }
+
+    #[test]
+    fn test_normalized() -> anyhow::Result<()> {
+        let mut part = Part::for_sample(0)?;
+        part.set_normalized(Param::HiCut, 0.75)
+            .set_normalized(Param::Pan, 0.0)
+            .set_normalized(Param::Speed, 0.5)
+            .set_normalized(Param::Length, 2.0)
+            .set_normalized(Param::Level, f32::NAN);
+        assert_eq!(part.param(Param::HiCut), 95);
+        assert_eq!(part.param(Param::Pan), 1);
+        assert_eq!(part.param(Param::Speed), 64);
+        assert_eq!(part.param(Param::Length), 127);
+        assert_eq!(part.param(Param::Level), 0);
+        assert_eq!(part.normalized(Param::Speed), 0.5);
+        assert_eq!(part.normalized(Param::Length), 1.0);
+
+        part.speed(Speed::Continuous(100))?;
+        assert_eq!(part.normalized(Param::Speed), 1.0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_motion_seq() -> anyhow::Result<()> {
+        let ramp = MotionSeq::ramp(0, 120).values();
+        assert_eq!(ramp[0], Some(0));
+        assert_eq!(ramp[1], Some(8));
+        assert_eq!(ramp[15], Some(120));
+        assert_eq!(
+            MotionSeq::steps(&[1, 2]).values()[..4],
+            [Some(1), Some(2), Some(1), Some(2)]
+        );
+        let sine = MotionSeq::sine(1.0, 0, 100).values();
+        assert_eq!(sine[0], Some(50));
+        assert_eq!(sine[4], Some(100));
+        assert_eq!(sine[12], Some(0));
+
+        // generated sequences are clamped to the parameter range
+        let part = Part::for_sample(0)?
+            .pan_start_motion_seq(MotionSeq::constant(0))?
+            .speed_start_motion_seq(MotionSeq::steps(&[0, 100, 120, 200]))?
+            .build();
+        assert_eq!(part.motion_values(Motion::Pan0), [Some(1); 16]);
+        assert_eq!(
+            part.motion_values(Motion::Speed0)[..4],
+            [Some(1), Some(100), Some(120), Some(127)]
+        );
+
+        // knob values are stored offset by 128, apart from speed
+        assert_eq!(part.data.Motion[Motion::Pan0 as usize], [129; 16]);
+        assert_eq!(
+            part.data.Motion[Motion::Speed0 as usize][..4],
+            [1, 100, 120, 127]
+        );
+        let part = Part::for_sample(0)?
+            .level_start_motion_seq(MotionSeq::ramp(0, 120))?
+            .hi_cut_motion_seq(MotionSeq::constant(0))?
+            .build();
+        assert_eq!(part.data.Motion[Motion::Level0 as usize][..2], [128, 136]);
+        assert_eq!(part.data.Motion[Motion::HiCut as usize], [128; 16]);
+        assert_eq!(part.motion_values(Motion::HiCut), [Some(0); 16]);
+
+        // speed sequences hold knob values
+        let speeds = [
+            Speed::Semitone(0),
+            Speed::Continuous(0),
+            Speed::Continuous(126),
+        ];
+        let mut sequence = [Speed::Semitone(-24); 16];
+        sequence[..3].copy_from_slice(&speeds);
+        let part = Part::for_sample(0)?
+            .speed_start_motion_seq(MotionSeq::try_from(sequence)?)?
+            .build();
+        assert_eq!(
+            part.motion_values(Motion::Speed0)[..4],
+            [Some(64), Some(1), Some(127), Some(40)]
+        );
+
+        // raw sequences are validated
+        assert!(Part::for_sample(0)?.pan_start_motion_seq([0; 16]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_motion_seq_no_value() -> anyhow::Result<()> {
+        let mut sparse = [None; 16];
+        sparse[3] = Some(100);
+        let part = Part::for_sample(0)?
+            .level_start_motion_seq(sparse)?
+            .hi_cut_motion_seq(*MotionSeq::empty().set(Step::Two, Some(200)))?
+            .build();
+
+        let level = part.data.Motion[Motion::Level0 as usize];
+        assert_eq!(level[3], 228);
+        assert_eq!(level.iter().filter(|&&v| v == MOTION_NO_VALUE).count(), 15);
+
+        let hi_cut = part.data.Motion[Motion::HiCut as usize];
+        assert_eq!(hi_cut[1], 255);
+        assert_eq!(hi_cut[0], MOTION_NO_VALUE);
+
+        sparse[4] = Some(1);
+        assert!(Part::for_sample(0)?.pan_start_motion_seq(sparse).is_ok());
+        sparse[4] = Some(0);
+        assert!(Part::for_sample(0)?.pan_start_motion_seq(sparse).is_err());
+        assert!(Part::for_sample(0)?.level_start_motion_seq(sparse).is_ok());
+        assert!(Part::for_sample(0)?.speed_start_motion_seq(sparse).is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_pattern_default() -> anyhow::Result<()> {
         let mut raw_bytes: Vec<u8> = vec![0; std::mem::size_of::<VolcaSample_Pattern_Data>()];
@@ -594,4 +2555,203 @@ mod test {
         let _data = pattern.to_bytes();
         Ok(())
     }
+
+    #[test]
+    fn test_text_grid() -> anyhow::Result<()> {
+        let mut level = [None; 16];
+        level[0] = Some(100);
+        level[1] = Some(50);
+        let mut pattern = Pattern::default();
+        pattern.with_part(
+            2,
+            Part::builder(42)?
+                .init_defaults()
+                .with_steps(Steps::from_indices(vec![0, 4])?)
+                .with_accents(Steps::from_indices(vec![4, 5])?)
+                .looped(On)
+                .pan(10)?
+                .level_start_motion_seq(level)?,
+        )?;
+        let grid = pattern.to_text_grid();
+        let lines: Vec<&str> = grid.lines().collect();
+        assert_eq!(lines.len(), 1 + 11 + 2);
+        assert_eq!(lines[0], "active xxxxxxxxxxxxxxxx");
+        assert_eq!(
+            lines[1],
+            "part smp steps            vol lvl pan spd aat adc pei pat pdc stp len hic fn"
+        );
+        assert_eq!(
+            lines[4],
+            "   2 042 x...Xa.......... 127 127  10  64   0 127  64   0 127   0 127 127 12"
+        );
+        assert_eq!(lines[12].len(), 20 + 16 * 4);
+        assert!(lines[13].starts_with("   2 level0          100  50   ."));
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_text_grid() -> anyhow::Result<()> {
+        let mut pattern = Pattern::default();
+        pattern.rotate_left(3);
+        pattern.with_part(
+            9,
+            Part::builder(7)?
+                .with_steps(Steps::from_indices(vec![2, 15])?)
+                .with_accents(Steps::from_indices(vec![1])?)
+                .reverse(On)
+                .hi_cut_motion_seq(MotionSeq::ramp(0, 127))?,
+        )?;
+        let grid = pattern.to_text_grid();
+        assert_eq!(Pattern::from_text_grid(&grid)?, pattern);
+
+        let edited = grid.replace("   9 007 .ax", "   9 012 xax");
+        let edited = Pattern::from_text_grid(&edited)?;
+        let part = edited.parts().nth(9).unwrap();
+        assert_eq!(part.sample_num(), 12);
+        assert_eq!(part.steps(), Steps::from_indices(vec![0, 2, 15])?);
+
+        let error = |line, column, reason: &str| {
+            Err(SyroError::InvalidTextGrid {
+                line,
+                column,
+                reason: reason.into(),
+            })
+        };
+        assert_eq!(
+            Pattern::from_text_grid("\nactive xxxxxxxxxxxxxxx"),
+            error(2, 8, "expected 16 steps, found 'xxxxxxxxxxxxxxx'")
+        );
+        assert_eq!(
+            Pattern::from_text_grid("  10 000"),
+            error(1, 3, "invalid part index '10', expected at most 9")
+        );
+        assert_eq!(
+            Pattern::from_text_grid("   1 level3"),
+            error(1, 6, "unknown motion 'level3'")
+        );
+        assert_eq!(
+            Pattern::from_text_grid("   1 001 x..?............"),
+            error(1, 13, "invalid step '?', expected one of 'Xxa.'")
+        );
+        assert_eq!(
+            Pattern::from_text_grid("   1 001 x..............."),
+            error(1, 26, "expected volume")
+        );
+        assert_eq!(
+            Pattern::from_text_grid("   1 100 x..............."),
+            error(1, 6, "invalid sample number '100', expected at most 99")
+        );
+        assert_eq!(
+            Pattern::from_text_grid(&grid.replacen(" 127 ", " 128 ", 1)),
+            error(3, 27, "invalid volume '128', out of range")
+        );
+        assert_eq!(
+            Pattern::from_text_grid("   1 hi_cut 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 200"),
+            error(1, 43, "invalid motion value '200', out of range")
+        );
+        assert_eq!(
+            Pattern::from_text_grid("active xxxxxxxxxxxxxxxx 1"),
+            error(1, 25, "unexpected value '1'")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_parts() -> anyhow::Result<()> {
+        let kick = Part::builder(0)?.with_steps(Steps::from_indices(vec![0, 8])?);
+        let snare = Part::builder(1)?.with_steps(Steps::from_indices(vec![4, 12])?);
+        let pattern = Pattern::from_parts(vec![(0, kick), (3, snare)])?;
+
+        let mut expected = Pattern::default();
+        expected.with_part(0, kick)?.with_part(3, snare)?;
+        assert_eq!(pattern, expected);
+        assert_eq!(
+            Pattern::with_parts([
+                Some(kick.build()),
+                None,
+                None,
+                Some(snare.build()),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None
+            ]),
+            expected
+        );
+        assert!(Pattern::from_parts(vec![(10, kick)]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_pattern_macro() -> anyhow::Result<()> {
+        let pattern = pattern! {
+            0 => part(3) { steps: "x...x...x...x..." },
+            2 => part(7) {
+                steps: "x.x. x.x. x.x. x.x.",
+                accents: "x--- ---- ---- ----",
+                level: 90,
+                looped: On,
+            },
+        }?;
+
+        let mut expected = Pattern::default();
+        expected
+            .with_part(
+                0,
+                Part::builder(3)?.with_steps(Steps::from_indices(vec![0, 4, 8, 12])?),
+            )?
+            .with_part(
+                2,
+                Part::builder(7)?
+                    .with_steps(Steps::from_indices((0..16).step_by(2))?)
+                    .with_accents(Steps::from_indices(vec![0])?)
+                    .level(90)?
+                    .looped(On),
+            )?;
+        assert_eq!(pattern, expected);
+
+        let level = 200;
+        assert!(pattern! { 0 => part(0) { level: level } }.is_err());
+        assert!(pattern! { 10 => part(0) {} }.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_pattern_from_bytes() -> anyhow::Result<()> {
+        let mut pattern = Pattern::default();
+        pattern.with_part(
+            4,
+            Part::for_sample(42)?
+                .with_steps(Steps::from_indices(vec![1, 3])?)
+                .level(100)?
+                .hi_cut_motion_seq(MotionSeq::ramp(0, 127))?
+                .build(),
+        )?;
+        let bytes = pattern.to_bytes();
+        assert_eq!(bytes.len(), PATTERN_SIZE);
+        assert_eq!(Pattern::from_bytes(&bytes)?.to_bytes(), bytes);
+
+        assert_eq!(
+            Pattern::from_bytes(&bytes[1..]).err().unwrap(),
+            SyroError::InvalidPatternSize {
+                size: PATTERN_SIZE - 1
+            }
+        );
+
+        let mut corrupt = bytes.clone();
+        corrupt[PATTERN_SIZE - 1] = 0;
+        assert_eq!(
+            Pattern::from_bytes(&corrupt).err().unwrap(),
+            SyroError::InvalidPatternHeader {
+                field: "footer",
+                found: PatternHeader::FOOTER & 0x00ff_ffff,
+                expected: PatternHeader::FOOTER
+            }
+        );
+        assert_eq!(PatternHeader::read(&bytes)?, PatternHeader::default());
+        assert_eq!(Pattern::default().header(), PatternHeader::default());
+        Ok(())
+    }
 }
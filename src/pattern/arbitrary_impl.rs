@@ -0,0 +1,95 @@
+//!
+//! Arbitrary support for the pattern types.
+//!
+//! Only values the device accepts are generated: sample numbers 0-99,
+//! parameters within their ranges and motion values clamped like the motion
+//! sequence setters do.
+//!
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+use super::*;
+
+impl<'a> Arbitrary<'a> for Steps {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Steps::from_mask(u.arbitrary()?))
+    }
+}
+
+impl<'a> Arbitrary<'a> for Speed {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        if u.arbitrary()? {
+            Ok(Speed::Semitone(u.int_in_range(-24..=24)?))
+        } else {
+            Ok(Speed::Continuous(
+                u.int_in_range(0..=SPEED_CONTINUOUS_VALUE_RANGE.1)?,
+            ))
+        }
+    }
+}
+
+// raw value within the range of the parameter
+fn arbitrary_param(u: &mut Unstructured<'_>, param: Param) -> Result<u8> {
+    if param == Param::Speed {
+        let speed: Speed = u.arbitrary()?;
+        return Ok(speed.to_raw().expect("generated speed is in range"));
+    }
+    let (lo, hi) = param.range();
+    u.int_in_range(lo..=hi)
+}
+
+impl<'a> Arbitrary<'a> for Part {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut data = part_init_data();
+        data.SampleNum = u.int_in_range(0..=SAMPLE_INDEX_RANGE.1 as u16)?;
+        data.StepOn = u.arbitrary()?;
+        data.Accent = u.arbitrary()?;
+        data.Level = u.int_in_range(LEVEL_RANGE.0..=LEVEL_RANGE.1)?;
+        for param in Param::ALL.iter() {
+            data.Param[param.index()] = arbitrary_param(u, *param)?;
+        }
+        // motion, loop, reverb, reverse and mute
+        data.FuncMemoryPart = u.int_in_range(0..=0x1f)?;
+        for motion in Motion::ALL.iter() {
+            for value in data.Motion[*motion as usize].iter_mut() {
+                *value = if u.arbitrary()? {
+                    motion.encode(motion.clamp(u.arbitrary()?))
+                } else {
+                    MOTION_NO_VALUE
+                };
+            }
+        }
+        Ok(Part { data })
+    }
+}
+
+impl<'a> Arbitrary<'a> for Pattern {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut pattern = Pattern::default();
+        pattern.data.ActiveStep = u.int_in_range(1..=u16::MAX)?;
+        for part in pattern.data.Part.iter_mut() {
+            *part = Part::arbitrary(u)?.data;
+        }
+        Ok(pattern)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_round_trips() -> anyhow::Result<()> {
+        let mut rng = Rng(7);
+        for _ in 0..32 {
+            let bytes: Vec<u8> = (0..8192).map(|_| rng.next_u64() as u8).collect();
+            let pattern = Pattern::arbitrary(&mut Unstructured::new(&bytes))?;
+            pattern.validate()?;
+            for part in pattern.parts() {
+                Speed::from_raw(part.param(Param::Speed))?;
+            }
+            assert_eq!(Pattern::from_bytes(&pattern.clone().to_bytes())?, pattern);
+            assert_eq!(Pattern::from_text_grid(&pattern.to_text_grid())?, pattern);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,193 @@
+//!
+//! Import of Standard MIDI File tracks into patterns.
+//!
+use std::collections::BTreeMap;
+
+use midly::{MidiMessage, Smf, Timing, TrackEventKind};
+
+use super::*;
+
+/// Maps MIDI notes to the parts of a pattern
+///
+/// # Examples
+///
+/// ```no_run
+/// use korg_syro::pattern::{MidiMapping, Pattern};
+///
+/// let bytes = std::fs::read("groove.mid").unwrap();
+/// let mut mapping = MidiMapping::default();
+/// mapping.note(36, 0)?.note(38, 1)?.note(42, 2)?;
+///
+/// let pattern = Pattern::from_midi(&bytes, 0, &mapping)?;
+/// # Ok::<(), korg_syro::SyroError>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct MidiMapping {
+    parts: BTreeMap<u8, u8>,
+    accent_velocity: u8,
+    velocity_to_level: bool,
+}
+
+impl Default for MidiMapping {
+    fn default() -> Self {
+        Self {
+            parts: BTreeMap::new(),
+            accent_velocity: 100,
+            velocity_to_level: false,
+        }
+    }
+}
+
+impl MidiMapping {
+    /// Mapping of the General MIDI drum notes to the first six parts
+    ///
+    /// Kick (36), snare (38), closed hi-hat (42), open hi-hat (46),
+    /// clap (39) and crash (49).
+    pub fn general_midi_drums() -> Self {
+        let mut mapping = Self::default();
+        for (part, &note) in [36, 38, 42, 46, 39, 49].iter().enumerate() {
+            mapping.parts.insert(note, part as u8);
+        }
+        mapping
+    }
+
+    /// Maps a note to the part at the given index, 0-9
+    pub fn note(&mut self, note: u8, part_index: u8) -> Result<&mut Self, SyroError> {
+        check_part_index(part_index)?;
+        self.parts.insert(note, part_index);
+        Ok(self)
+    }
+
+    /// Notes at or above this velocity are accented, defaults to 100
+    pub fn accent_velocity(&mut self, velocity: u8) -> &mut Self {
+        self.accent_velocity = velocity;
+        self
+    }
+
+    /// Records note velocities in the level motion sequence of the part
+    pub fn velocity_to_level(&mut self, value: Toggle) -> &mut Self {
+        self.velocity_to_level = matches!(value, Toggle::On);
+        self
+    }
+}
+
+impl Pattern {
+    /// Builds a pattern from a track of a Standard MIDI File
+    ///
+    /// Notes are quantized to 16th notes and only the first bar of 4/4 is
+    /// used. Notes that are not part of the mapping are ignored, as are the
+    /// parts without any mapped notes, which keep their default values.
+    pub fn from_midi(bytes: &[u8], track: usize, mapping: &MidiMapping) -> Result<Self, SyroError> {
+        let invalid = |reason: String| SyroError::InvalidMidi { reason };
+        let smf = Smf::parse(bytes).map_err(|e| invalid(e.to_string()))?;
+        let ticks_per_beat = match smf.header.timing {
+            Timing::Metrical(ticks) => ticks.as_int() as u32,
+            Timing::Timecode(..) => return Err(invalid("timecode timing is not supported".into())),
+        };
+        let events = smf
+            .tracks
+            .get(track)
+            .ok_or_else(|| invalid(format!("no track {}", track)))?;
+
+        let ticks_per_step = ticks_per_beat as f64 / 4.0;
+        let mut pattern = Self::default();
+        let mut tick = 0u32;
+        for event in events.iter() {
+            tick += event.delta.as_int();
+            let (key, vel) = match event.kind {
+                TrackEventKind::Midi {
+                    message: MidiMessage::NoteOn { key, vel },
+                    ..
+                } if vel.as_int() > 0 => (key.as_int(), vel.as_int()),
+                _ => continue,
+            };
+            let part_index = match mapping.parts.get(&key) {
+                Some(&index) => index as usize,
+                None => continue,
+            };
+            let step = (tick as f64 / ticks_per_step).round() as usize;
+            if step >= 16 {
+                continue;
+            }
+
+            let part = &mut pattern.data.Part[part_index];
+            part.StepOn |= 1 << step;
+            if vel >= mapping.accent_velocity {
+                part.Accent |= 1 << step;
+            }
+            if mapping.velocity_to_level {
+                let level = &mut part.Motion[Motion::Level0 as usize][step];
+                *level = (*level).max(Motion::Level0.encode(vel));
+                part.FuncMemoryPart |= VOLCASAMPLE_FUNC_MOTION as u8;
+            }
+        }
+
+        Ok(pattern)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use midly::num::{u4, u7};
+    use midly::{Format, Header, MetaMessage, TrackEvent};
+
+    fn note_on(delta: u32, key: u8, vel: u8) -> TrackEvent<'static> {
+        TrackEvent {
+            delta: delta.into(),
+            kind: TrackEventKind::Midi {
+                channel: u4::new(9),
+                message: MidiMessage::NoteOn {
+                    key: u7::new(key),
+                    vel: u7::new(vel),
+                },
+            },
+        }
+    }
+
+    fn midi_file() -> Vec<u8> {
+        let mut smf = Smf::new(Header::new(
+            Format::SingleTrack,
+            Timing::Metrical(96.into()),
+        ));
+        smf.tracks.push(vec![
+            // kick on every beat, slightly off the grid
+            note_on(0, 36, 127),
+            note_on(95, 36, 80),
+            // snare on the 2nd beat
+            note_on(1, 38, 64),
+            // unmapped note
+            note_on(0, 60, 64),
+            note_on(94, 36, 80),
+            note_on(100, 36, 80),
+            // next bar
+            note_on(94, 36, 80),
+            TrackEvent {
+                delta: 0.into(),
+                kind: TrackEventKind::Meta(MetaMessage::EndOfTrack),
+            },
+        ]);
+        let mut bytes = vec![];
+        smf.write_std(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn from_midi() -> anyhow::Result<()> {
+        let mut mapping = MidiMapping::general_midi_drums();
+        mapping.velocity_to_level(Toggle::On);
+
+        let pattern = Pattern::from_midi(&midi_file(), 0, &mapping)?;
+        let parts: Vec<Part> = pattern.parts().collect();
+        assert_eq!(parts[0].to_string(), "000 X...x...x...x...");
+        assert_eq!(parts[1].to_string(), "000 ....x...........");
+        assert_eq!(parts[2].to_string(), "000 ................");
+        let level = parts[1].motion_values(Motion::Level0);
+        assert_eq!(level[4], Some(64));
+        assert_eq!(level[0], None);
+
+        assert!(Pattern::from_midi(&midi_file(), 1, &mapping).is_err());
+        assert!(Pattern::from_midi(&[0, 1, 2], 0, &mapping).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,316 @@
+//!
+//! Serde support for the pattern types.
+//!
+//! Rather than mirroring the raw C structs, parts are represented by their
+//! parameter names, step sequences as grid strings (see the [Display](std::fmt::Display)
+//! implementation of [Steps]), and motion sequences as maps from parameter
+//! name to 16 knob values, where `null` marks an untouched step.
+//!
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::de::{self, Deserializer, Visitor};
+use serde::ser::Serializer;
+use serde::{Deserialize, Serialize};
+
+use super::*;
+
+// names of the motion sequences, ordered by their index in the part data
+const MOTION_NAMES: [&str; 14] = [
+    "level_start",
+    "level_end",
+    "pan_start",
+    "pan_end",
+    "speed_start",
+    "speed_end",
+    "amp_eg_attack",
+    "amp_eg_decay",
+    "pitch_eg_int",
+    "pitch_eg_attack",
+    "pitch_eg_decay",
+    "start_point",
+    "length",
+    "hi_cut",
+];
+
+impl Serialize for Steps {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+struct StepsVisitor;
+
+impl<'de> Visitor<'de> for StepsVisitor {
+    type Value = Steps;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a string of 16 steps, `x` for on and `.` for off")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Steps, E> {
+        if value.chars().count() != 16 {
+            return Err(E::invalid_value(de::Unexpected::Str(value), &self));
+        }
+        let mut steps = Steps::builder();
+        for (index, c) in value.chars().enumerate() {
+            match c {
+                'x' | 'X' => steps.steps |= 1 << index,
+                '.' => {}
+                _ => return Err(E::invalid_value(de::Unexpected::Str(value), &self)),
+            }
+        }
+        Ok(steps)
+    }
+}
+
+impl<'de> Deserialize<'de> for Steps {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_str(StepsVisitor)
+    }
+}
+
+// fields other than the sample number default to those of Part::for_sample
+#[derive(Serialize, Deserialize)]
+struct PartRepr {
+    sample: u16,
+    #[serde(default)]
+    steps: Steps,
+    #[serde(default)]
+    accents: Steps,
+    #[serde(default)]
+    level: u8,
+    #[serde(default)]
+    pan: u8,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    speed: Option<Speed>,
+    #[serde(default)]
+    amp_eg_attack: u8,
+    #[serde(default)]
+    amp_eg_decay: u8,
+    #[serde(default)]
+    pitch_eg_int: u8,
+    #[serde(default)]
+    pitch_eg_attack: u8,
+    #[serde(default)]
+    pitch_eg_decay: u8,
+    #[serde(default)]
+    starting_point: u8,
+    #[serde(default)]
+    length: u8,
+    #[serde(default)]
+    hi_cut: u8,
+    #[serde(default)]
+    motion: bool,
+    #[serde(default)]
+    looped: bool,
+    #[serde(default)]
+    reverb: bool,
+    #[serde(default)]
+    reverse: bool,
+    #[serde(default)]
+    mute: bool,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    motion_sequences: BTreeMap<String, [Option<u8>; 16]>,
+}
+
+impl From<&Part> for PartRepr {
+    fn from(part: &Part) -> Self {
+        let data = &part.data;
+        let param = |index: u32| data.Param[index as usize];
+        let func = |func: u32| data.FuncMemoryPart & func as u8 != 0;
+
+        let mut motion_sequences = BTreeMap::new();
+        for (name, &motion) in MOTION_NAMES.iter().zip(Motion::ALL.iter()) {
+            let values = part.motion_values(motion);
+            if values.iter().any(Option::is_some) {
+                motion_sequences.insert(name.to_string(), values);
+            }
+        }
+
+        Self {
+            sample: data.SampleNum,
+            steps: Steps { steps: data.StepOn },
+            accents: Steps { steps: data.Accent },
+            level: param(VOLCASAMPLE_PARAM_LEVEL),
+            pan: param(VOLCASAMPLE_PARAM_PAN),
+            // zero-initialized parts have no valid speed
+            speed: Speed::from_raw(param(VOLCASAMPLE_PARAM_SPEED)).ok(),
+            amp_eg_attack: param(VOLCASAMPLE_PARAM_AMPEG_ATTACK),
+            amp_eg_decay: param(VOLCASAMPLE_PARAM_AMPEG_DECAY),
+            pitch_eg_int: param(VOLCASAMPLE_PARAM_PITCHEG_INT),
+            pitch_eg_attack: param(VOLCASAMPLE_PARAM_PITCHEG_ATTACK),
+            pitch_eg_decay: param(VOLCASAMPLE_PARAM_PITCHEG_DECAY),
+            starting_point: param(VOLCASAMPLE_PARAM_START_POINT),
+            length: param(VOLCASAMPLE_PARAM_LENGTH),
+            hi_cut: param(VOLCASAMPLE_PARAM_HICUT),
+            motion: func(VOLCASAMPLE_FUNC_MOTION),
+            looped: func(VOLCASAMPLE_FUNC_LOOP),
+            reverb: func(VOLCASAMPLE_FUNC_REVERB),
+            reverse: func(VOLCASAMPLE_FUNC_REVERSE),
+            mute: func(VOLCASAMPLE_FUNC_MUTE),
+            motion_sequences,
+        }
+    }
+}
+
+impl TryFrom<PartRepr> for Part {
+    type Error = String;
+
+    fn try_from(repr: PartRepr) -> Result<Self, Self::Error> {
+        let mut part = Part::for_sample(repr.sample).map_err(|e| e.to_string())?;
+        let data = &mut part.data;
+        data.StepOn = repr.steps.to_bytes();
+        data.Accent = repr.accents.to_bytes();
+
+        let mut params = [
+            (VOLCASAMPLE_PARAM_LEVEL, repr.level),
+            (VOLCASAMPLE_PARAM_PAN, repr.pan),
+            (VOLCASAMPLE_PARAM_AMPEG_ATTACK, repr.amp_eg_attack),
+            (VOLCASAMPLE_PARAM_AMPEG_DECAY, repr.amp_eg_decay),
+            (VOLCASAMPLE_PARAM_PITCHEG_INT, repr.pitch_eg_int),
+            (VOLCASAMPLE_PARAM_PITCHEG_ATTACK, repr.pitch_eg_attack),
+            (VOLCASAMPLE_PARAM_PITCHEG_DECAY, repr.pitch_eg_decay),
+            (VOLCASAMPLE_PARAM_START_POINT, repr.starting_point),
+            (VOLCASAMPLE_PARAM_LENGTH, repr.length),
+            (VOLCASAMPLE_PARAM_HICUT, repr.hi_cut),
+        ]
+        .to_vec();
+        if let Some(speed) = repr.speed {
+            let raw = speed.to_raw().map_err(|e| e.to_string())?;
+            params.push((VOLCASAMPLE_PARAM_SPEED, raw));
+        }
+        for (index, value) in params {
+            data.Param[index as usize] = value;
+        }
+
+        for (func, on) in [
+            (VOLCASAMPLE_FUNC_MOTION, repr.motion),
+            (VOLCASAMPLE_FUNC_LOOP, repr.looped),
+            (VOLCASAMPLE_FUNC_REVERB, repr.reverb),
+            (VOLCASAMPLE_FUNC_REVERSE, repr.reverse),
+            (VOLCASAMPLE_FUNC_MUTE, repr.mute),
+        ]
+        .iter()
+        {
+            if *on {
+                data.FuncMemoryPart |= *func as u8;
+            }
+        }
+
+        for (name, values) in repr.motion_sequences {
+            let index = MOTION_NAMES
+                .iter()
+                .position(|&n| n == name)
+                .ok_or_else(|| format!("unknown motion sequence '{}'", name))?;
+            data.Motion[index] = MotionSeq::from(values)
+                .resolve(Motion::ALL[index])
+                .map_err(|e| e.to_string())?;
+        }
+
+        Ok(part)
+    }
+}
+
+impl Serialize for Part {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        PartRepr::from(self).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Part {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = PartRepr::deserialize(deserializer)?;
+        Part::try_from(repr).map_err(de::Error::custom)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct PatternRepr {
+    active_steps: Steps,
+    parts: [Part; 10],
+}
+
+impl Serialize for Pattern {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut parts = [Part::for_sample(0).unwrap(); 10];
+        for (part, data) in parts.iter_mut().zip(self.data.Part.iter()) {
+            part.data = *data;
+        }
+        PatternRepr {
+            active_steps: Steps {
+                steps: self.data.ActiveStep,
+            },
+            parts,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Pattern {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = PatternRepr::deserialize(deserializer)?;
+        let mut pattern = Pattern::default();
+        pattern.data.ActiveStep = repr.active_steps.to_bytes();
+        for (data, part) in pattern.data.Part.iter_mut().zip(repr.parts.iter()) {
+            *data = part.data;
+        }
+        Ok(pattern)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Toggle::*;
+    use super::*;
+
+    #[test]
+    fn part_round_trip() -> anyhow::Result<()> {
+        let part = Part::for_sample(12)?
+            .with_steps(Steps::from_indices(vec![0, 4, 8, 12])?)
+            .level(100)?
+            .speed(Speed::Semitone(-3))?
+            .reverb(On)
+            .level_start_motion_seq(*MotionSeq::empty().set(Step::Five, Some(42)))?
+            .build();
+
+        let json = serde_json::to_value(part)?;
+        assert_eq!(json["sample"], 12);
+        assert_eq!(json["steps"], "x...x...x...x...");
+        assert_eq!(json["speed"]["mode"], "semitone");
+        assert_eq!(json["speed"]["value"], -3);
+        assert_eq!(json["reverb"], true);
+        assert_eq!(json["motion_sequences"]["level_start"][4], 42);
+        assert!(json["motion_sequences"]["level_start"][0].is_null());
+
+        let restored: Part = serde_json::from_value(json)?;
+        assert_eq!(restored.data.Param, part.data.Param);
+        assert_eq!(restored.data.Motion, part.data.Motion);
+        assert_eq!(restored.data.StepOn, part.data.StepOn);
+        assert_eq!(restored.data.FuncMemoryPart, part.data.FuncMemoryPart);
+        Ok(())
+    }
+
+    #[test]
+    fn pattern_round_trip() -> anyhow::Result<()> {
+        let mut pattern = Pattern::default();
+        pattern.with_part(
+            3,
+            Part::for_sample(42)?
+                .with_steps(Steps::from_indices(0..4)?)
+                .build(),
+        )?;
+
+        let json = serde_json::to_string(&pattern)?;
+        let restored: Pattern = serde_json::from_str(&json)?;
+        assert_eq!(restored.data.Part[3].SampleNum, 42);
+        assert_eq!(restored.to_string(), pattern.to_string());
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_steps() {
+        let result: Result<Steps, _> = serde_json::from_str("\"x...\"");
+        assert!(result.is_err());
+    }
+}
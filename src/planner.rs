@@ -0,0 +1,181 @@
+//! Capacity-aware allocation of sample slots
+use crate::{check_sample_index, DeviceProfile, SlotId, SyroError, VolcaSample, MAX_SAMPLE_SIZE};
+
+const NUM_SLOTS: usize = VolcaSample::SAMPLE_SLOTS;
+
+/// Defines how [SlotPlanner::allocate] picks a free slot
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum FitStrategy {
+    /// The lowest free slot
+    #[default]
+    FirstFit,
+    /// A slot from the shortest run of consecutive free slots, keeping long
+    /// runs available for kits that should occupy neighbouring slots
+    BestFit,
+}
+
+/// Tracks the occupied sample slots and the remaining sample memory
+///
+/// Sizes are in bytes as sent to the device, so the size of a compressed
+/// sample has to be estimated by the caller.
+#[derive(Clone, Debug)]
+pub struct SlotPlanner {
+    slots: [Option<usize>; NUM_SLOTS],
+    reserved: [bool; NUM_SLOTS],
+    budget: usize,
+    strategy: FitStrategy,
+}
+
+impl Default for SlotPlanner {
+    fn default() -> Self {
+        Self {
+            slots: [None; NUM_SLOTS],
+            reserved: [false; NUM_SLOTS],
+            budget: MAX_SAMPLE_SIZE,
+            strategy: FitStrategy::default(),
+        }
+    }
+}
+
+impl SlotPlanner {
+    /// Sets the total sample memory in bytes, defaults to [MAX_SAMPLE_SIZE]
+    pub fn budget(&mut self, bytes: usize) -> &mut Self {
+        self.budget = bytes;
+        self
+    }
+
+    pub fn strategy(&mut self, strategy: FitStrategy) -> &mut Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Keeps a slot from being picked by [allocate](SlotPlanner::allocate),
+    /// it can still be filled with [occupy](SlotPlanner::occupy)
+    pub fn reserve(&mut self, index: u8) -> Result<&mut Self, SyroError> {
+        check_sample_index(index)?;
+        self.reserved[index as usize] = true;
+        Ok(self)
+    }
+
+    /// Marks a slot as holding a sample of the given size, for example one that
+    /// is already on the device
+    pub fn occupy(&mut self, index: u8, size: usize) -> Result<&mut Self, SyroError> {
+        check_sample_index(index)?;
+        let available = self.remaining() + self.slots[index as usize].unwrap_or(0);
+        if size > available {
+            return Err(SyroError::InsufficientMemory { size, available });
+        }
+        self.slots[index as usize] = Some(size);
+        Ok(self)
+    }
+
+    /// Frees a slot and its reservation
+    pub fn release(&mut self, index: u8) -> Result<&mut Self, SyroError> {
+        check_sample_index(index)?;
+        self.slots[index as usize] = None;
+        self.reserved[index as usize] = false;
+        Ok(self)
+    }
+
+    /// Picks a free slot for a sample of the given size in bytes
+    pub fn allocate(&mut self, size: usize) -> Result<SlotId, SyroError> {
+        let available = self.remaining();
+        if size > available {
+            return Err(SyroError::InsufficientMemory { size, available });
+        }
+        let index = match self.strategy {
+            FitStrategy::FirstFit => (0..NUM_SLOTS).find(|&i| self.is_available(i)),
+            FitStrategy::BestFit => self.best_fit(),
+        }
+        .ok_or(SyroError::NoFreeSlot)?;
+        self.slots[index] = Some(size);
+        Ok(SlotId::Sample(index as u8))
+    }
+
+    /// Picks a free slot for the given 16 bit PCM data
+    pub fn allocate_sample(&mut self, data: &[i16]) -> Result<SlotId, SyroError> {
+        self.allocate(data.len() * 2)
+    }
+
+    /// Returns the sample memory left in bytes
+    pub fn remaining(&self) -> usize {
+        let used: usize = self.slots.iter().flatten().sum();
+        self.budget.saturating_sub(used)
+    }
+
+    /// Returns true if the slot is neither occupied nor reserved
+    pub fn is_free(&self, index: u8) -> bool {
+        (index as usize) < NUM_SLOTS && self.is_available(index as usize)
+    }
+
+    fn is_available(&self, index: usize) -> bool {
+        self.slots[index].is_none() && !self.reserved[index]
+    }
+
+    // first slot of the shortest run of available slots
+    fn best_fit(&self) -> Option<usize> {
+        let mut best: Option<(usize, usize)> = None;
+        let mut index = 0;
+        while index < NUM_SLOTS {
+            if !self.is_available(index) {
+                index += 1;
+                continue;
+            }
+            let start = index;
+            while index < NUM_SLOTS && self.is_available(index) {
+                index += 1;
+            }
+            let len = index - start;
+            match best {
+                Some((_, best_len)) if best_len <= len => {}
+                _ => best = Some((start, len)),
+            }
+        }
+        best.map(|(start, _)| start)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_fit() -> anyhow::Result<()> {
+        let mut planner = SlotPlanner::default();
+        planner.budget(1000).reserve(0)?.occupy(1, 100)?;
+        assert_eq!(planner.allocate(200)?, SlotId::Sample(2));
+        assert_eq!(planner.allocate_sample(&[0; 100])?, SlotId::Sample(3));
+        assert_eq!(planner.remaining(), 500);
+        assert!(!planner.is_free(0));
+        assert_eq!(
+            planner.allocate(501),
+            Err(SyroError::InsufficientMemory {
+                size: 501,
+                available: 500
+            })
+        );
+
+        planner.release(2)?;
+        assert_eq!(planner.allocate(100)?, SlotId::Sample(2));
+        Ok(())
+    }
+
+    #[test]
+    fn best_fit() -> anyhow::Result<()> {
+        let mut planner = SlotPlanner::default();
+        planner
+            .strategy(FitStrategy::BestFit)
+            .occupy(2, 0)?
+            .occupy(5, 0)?;
+        // free runs are 0-1, 3-4 and 6-99
+        assert_eq!(planner.allocate(0)?, SlotId::Sample(0));
+        assert_eq!(planner.allocate(0)?, SlotId::Sample(1));
+        assert_eq!(planner.allocate(0)?, SlotId::Sample(3));
+
+        for index in 0..100 {
+            planner.occupy(index, 0)?;
+        }
+        assert_eq!(planner.allocate(0), Err(SyroError::NoFreeSlot));
+        Ok(())
+    }
+}
@@ -0,0 +1,310 @@
+//! Approximate rendering of how parts and patterns sound on the device
+//!
+//! Useful for auditioning before sending a transfer that can take minutes.
+//! The rendering covers speed, level, pan, start point, length, reverse, loop,
+//! the amp EG, accents and motion sequences. Pitch EG, hi cut and reverb are
+//! not applied, and envelope times, accents and continuous speed only
+//! approximate the device.
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::pattern::{Motion, Param, Part, Pattern, Speed};
+
+// envelope time at the maximum parameter value
+const MAX_ATTACK_SECS: f64 = 1.0;
+const MAX_DECAY_SECS: f64 = 2.0;
+
+// level of steps without an accent relative to accented steps
+const UNACCENTED_GAIN: f64 = 0.7;
+
+/// Renders a sample as played by the part
+///
+/// `sample` is mono PCM at `sample_rate`, the output is `duration` long at the
+/// same rate and silent once the sample has finished playing.
+pub fn render(sample: &[i16], sample_rate: u32, part: &Part, duration: Duration) -> Vec<i16> {
+    let frames = (duration.as_secs_f64() * sample_rate as f64).round() as usize;
+    Voice::new(part)
+        .render(sample, sample_rate, frames)
+        .into_iter()
+        .map(|value| value.round() as i16)
+        .collect()
+}
+
+/// Renders a pattern as interleaved stereo PCM at `sample_rate`
+///
+/// Each part plays its sample from `samples`, keyed by sample number, which
+/// must be at `sample_rate` too. Parts whose sample is missing stay silent.
+/// Steps are sixteenth notes at `bpm`, and a bar is one pass over the active
+/// steps of the pattern. A part stops playing when it is triggered again.
+pub fn render_pattern(
+    pattern: &Pattern,
+    samples: &BTreeMap<u16, Vec<i16>>,
+    sample_rate: u32,
+    bpm: f32,
+    bars: u32,
+) -> Vec<i16> {
+    let active_steps = pattern.active_steps().to_bytes();
+    let steps: Vec<usize> = (0..16).filter(|s| active_steps & 1 << s != 0).collect();
+    let step_frames = 60.0 / bpm as f64 / 4.0 * sample_rate as f64;
+    let total_steps = steps.len() * bars as usize;
+    let step_start = |n: usize| (n as f64 * step_frames).round() as usize;
+    let total_frames = step_start(total_steps);
+    let mut mix = vec![0.0; total_frames * 2];
+
+    for part in pattern.parts() {
+        let sample = match samples.get(&part.sample_num()) {
+            Some(sample) => sample,
+            None => continue,
+        };
+        let (part_steps, accents) = (part.steps().to_bytes(), part.accents().to_bytes());
+        let triggers: Vec<usize> = (0..total_steps)
+            .filter(|n| part_steps & 1 << steps[n % steps.len()] != 0)
+            .collect();
+
+        for (i, &n) in triggers.iter().enumerate() {
+            let step = steps[n % steps.len()];
+            let mut voice = Voice::new(&part);
+            if part.is_motion_enabled() {
+                for &motion in Motion::ALL.iter() {
+                    if let Some(value) = part.motion_values(motion)[step] {
+                        voice.apply(motion, value);
+                    }
+                }
+            }
+            if accents & 1 << step == 0 {
+                voice.gain *= UNACCENTED_GAIN;
+            }
+
+            let start = step_start(n);
+            let end = triggers
+                .get(i + 1)
+                .map_or(total_frames, |&next| step_start(next));
+            let (left, right) = voice.pan_gains();
+            let rendered = voice.render(sample, sample_rate, end - start);
+            for (frame, value) in mix[start * 2..end * 2].chunks_exact_mut(2).zip(rendered) {
+                frame[0] += value * left;
+                frame[1] += value * right;
+            }
+        }
+    }
+
+    mix.into_iter()
+        .map(|value| value.round().clamp(i16::MIN as f64, i16::MAX as f64) as i16)
+        .collect()
+}
+
+// playback settings of a single trigger of a part
+struct Voice {
+    gain: f64,
+    pan: u8,
+    speed: u8,
+    start_point: u8,
+    length: u8,
+    attack: u8,
+    decay: u8,
+    looped: bool,
+    reversed: bool,
+}
+
+impl Voice {
+    fn new(part: &Part) -> Self {
+        Self {
+            gain: part.param(Param::Level) as f64 / 127.0,
+            pan: part.param(Param::Pan),
+            speed: part.param(Param::Speed),
+            start_point: part.param(Param::StartPoint),
+            length: part.param(Param::Length),
+            attack: part.param(Param::AmpEgAttack),
+            decay: part.param(Param::AmpEgDecay),
+            looped: part.is_looped(),
+            reversed: part.is_reversed(),
+        }
+    }
+
+    // applies the value of a motion sequence at the start of a step
+    fn apply(&mut self, motion: Motion, value: u8) {
+        match motion {
+            Motion::Level0 => self.gain = value as f64 / 127.0,
+            Motion::Pan0 => self.pan = value,
+            Motion::Speed0 => self.speed = value,
+            Motion::StartPoint => self.start_point = value,
+            Motion::Length => self.length = value,
+            Motion::AmpEgAttack => self.attack = value,
+            Motion::AmpEgDecay => self.decay = value,
+            _ => {}
+        }
+    }
+
+    // constant power pan, 64 is the center
+    fn pan_gains(&self) -> (f64, f64) {
+        let angle = (self.pan.clamp(1, 127) - 1) as f64 / 126.0 * std::f64::consts::FRAC_PI_2;
+        (angle.cos(), angle.sin())
+    }
+
+    fn render(&self, sample: &[i16], sample_rate: u32, frames: usize) -> Vec<f64> {
+        let mut output = vec![0.0; frames];
+
+        let len = sample.len();
+        let start = len * self.start_point.min(127) as usize / 127;
+        let end = start + (len - start) * self.length.min(127) as usize / 127;
+        if end <= start {
+            return output;
+        }
+        let region = &sample[start..end];
+        let ratio = speed_ratio(self.speed);
+        let envelope = Envelope::new(self.attack, self.decay, sample_rate);
+
+        let mut position = 0.0;
+        for (i, out) in output.iter_mut().enumerate() {
+            if position >= region.len() as f64 {
+                if !self.looped {
+                    break;
+                }
+                position %= region.len() as f64;
+            }
+            *out = interpolate(region, position, self.reversed) * self.gain * envelope.at(i);
+            position += ratio;
+        }
+        output
+    }
+}
+
+// playback rate relative to the original pitch
+fn speed_ratio(raw: u8) -> f64 {
+    match Speed::from_raw(raw) {
+        Ok(Speed::Semitone(offset)) => 2f64.powf(offset as f64 / 12.0),
+        // the continuous range is taken to span the same two octaves
+        Ok(Speed::Continuous(value)) => 2f64.powf((value as f64 - 63.0) / 63.0 * 2.0),
+        Err(_) => 1.0,
+    }
+}
+
+fn interpolate(region: &[i16], position: f64, reversed: bool) -> f64 {
+    let last = region.len() - 1;
+    let index = position as usize;
+    let fraction = position - index as f64;
+    let at = |i: usize| {
+        let i = i.min(last);
+        region[if reversed { last - i } else { i }] as f64
+    };
+    at(index) + (at(index + 1) - at(index)) * fraction
+}
+
+// linear attack up to full level followed by a linear decay, a decay of 127
+// holds the level
+struct Envelope {
+    attack: f64,
+    decay: Option<f64>,
+}
+
+impl Envelope {
+    fn new(attack: u8, decay: u8, sample_rate: u32) -> Self {
+        let frames = |value: u8, max_secs: f64| {
+            (value as f64 / 127.0).powi(2) * max_secs * sample_rate as f64
+        };
+        Self {
+            attack: frames(attack, MAX_ATTACK_SECS),
+            decay: if decay < 127 {
+                Some(frames(decay, MAX_DECAY_SECS))
+            } else {
+                None
+            },
+        }
+    }
+
+    fn at(&self, frame: usize) -> f64 {
+        let frame = frame as f64;
+        if frame < self.attack {
+            return frame / self.attack;
+        }
+        match self.decay {
+            Some(decay) if decay > 0.0 => (1.0 - (frame - self.attack) / decay).max(0.0),
+            Some(_) => 0.0,
+            None => 1.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern::{MotionSeq, Steps, Toggle};
+
+    fn ramp() -> Vec<i16> {
+        (0..1000).map(|i| i as i16 * 10).collect()
+    }
+
+    #[test]
+    fn test_render() -> anyhow::Result<()> {
+        let duration = Duration::from_millis(100);
+        let mut part = Part::for_sample(0)?;
+        part.init_defaults();
+        assert_eq!(render(&ramp(), 10000, &part, duration), ramp());
+
+        part.reverse(Toggle::On);
+        let reversed: Vec<i16> = ramp().into_iter().rev().collect();
+        assert_eq!(render(&ramp(), 10000, &part, duration), reversed);
+
+        // an octave up plays the sample in half the time
+        part.reverse(Toggle::Off).speed(Speed::Semitone(12))?;
+        let output = render(&ramp(), 10000, &part, duration);
+        assert_eq!(output[499], 9980);
+        assert!(output[500..].iter().all(|&s| s == 0));
+
+        part.speed(Speed::Semitone(0))?.level(0)?;
+        assert!(render(&ramp(), 10000, &part, duration)
+            .iter()
+            .all(|&s| s == 0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_pattern() -> anyhow::Result<()> {
+        let mut pattern = Pattern::default();
+        pattern.with_part(
+            0,
+            *Part::for_sample(7)?
+                .init_defaults()
+                .with_steps(Steps::from_indices(vec![0, 8])?)
+                .with_accents(Steps::from_indices(vec![8])?),
+        )?;
+        let mut samples = BTreeMap::new();
+        samples.insert(7, vec![10000; 100]);
+
+        // 120 bpm at 1kHz gives 125 frames per step
+        let output = render_pattern(&pattern, &samples, 1000, 120.0, 1);
+        assert_eq!(output.len(), 16 * 125 * 2);
+        let center = std::f64::consts::FRAC_1_SQRT_2;
+        let unaccented = (10000.0 * UNACCENTED_GAIN * center).round() as i16;
+        assert_eq!(&output[..2], &[unaccented, unaccented]);
+        assert!(output[200..1000 * 2].iter().all(|&s| s == 0));
+        let accented = (10000.0 * center).round() as i16;
+        assert_eq!(&output[1000 * 2..1000 * 2 + 2], &[accented, accented]);
+
+        // motion sequences override the part parameters on their step
+        let mut part = pattern.parts().next().unwrap();
+        part.motion(Toggle::On)
+            .pan_start_motion_seq(MotionSeq::steps(&[127]))?;
+        pattern.with_part(0, part)?;
+        let output = render_pattern(&pattern, &samples, 1000, 120.0, 1);
+        assert_eq!(output[0], 0);
+        assert_eq!(output[1], (10000.0 * UNACCENTED_GAIN).round() as i16);
+
+        assert!(render_pattern(&pattern, &BTreeMap::new(), 1000, 120.0, 1)
+            .iter()
+            .all(|&s| s == 0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_loop() -> anyhow::Result<()> {
+        let mut part = Part::for_sample(0)?;
+        part.init_defaults().looped(Toggle::On).starting_point(64)?;
+        let output = render(&ramp(), 10000, &part, Duration::from_millis(200));
+        assert_eq!(output.len(), 2000);
+        // the second half of the ramp played four times
+        assert_eq!(output[0], 5030);
+        assert_eq!(output[497], output[0]);
+        Ok(())
+    }
+}
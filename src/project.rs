@@ -0,0 +1,835 @@
+//!
+//! Declarative project manifests describing the contents of a syro stream.
+//!
+//! A manifest lists samples to load from .wav files, slots to erase and
+//! patterns to program, and can be written in TOML or JSON.
+//!
+//! # Examples
+//!
+//! ```toml
+//! erase = [10, 11]
+//!
+//! [[samples]]
+//! slot = 0
+//! path = "kick.wav"
+//!
+//! [[samples]]
+//! slot = 1
+//! path = "snare.wav"
+//! compression = 12
+//!
+//! [[patterns]]
+//! slot = 0
+//!
+//! [[patterns.parts]]
+//! index = 0
+//! sample = 0
+//! steps = "x...x...x...x..."
+//!
+//! [[patterns.parts]]
+//! index = 1
+//! sample = 1
+//! steps = "....x.......x..."
+//! accents = "............x..."
+//! ```
+//!
+//! ```no_run
+//! use korg_syro::project::Project;
+//!
+//! let project = Project::load("kit.toml")?;
+//! let data = project.to_syro_stream()?.generate()?;
+//! # Ok::<(), korg_syro::project::ProjectError>(())
+//! ```
+//!
+//! A stream built in code can be saved as a project, which writes the
+//! manifest along with a .wav file for every sample.
+//!
+//! ```no_run
+//! use korg_syro::SyroStream;
+//! use korg_syro::project::Project;
+//!
+//! let mut syro_stream = SyroStream::default();
+//! syro_stream.add_sample(0, vec![0, 1000, 0, -1000], 44100, Some(12))?;
+//!
+//! Project::from_syro_stream(&syro_stream)?.save("kit.toml")?;
+//! # Ok::<(), korg_syro::project::ProjectError>(())
+//! ```
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::{Path, PathBuf};
+
+use byteorder::{ByteOrder, LittleEndian};
+use korg_syro_sys as syro;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::pattern::{Part, Pattern};
+use crate::{AddSampleOptions, DeviceProfile, SyroError, SyroStream, VolcaSample};
+
+#[derive(Error, Debug)]
+pub enum ProjectError {
+    #[error("failed to read {path}: {source}")]
+    Io { path: PathBuf, source: io::Error },
+
+    #[error("failed to write {path}: {source}")]
+    Write { path: PathBuf, source: io::Error },
+
+    #[error("failed to parse manifest: {0}")]
+    Parse(String),
+
+    #[error("failed to serialize manifest: {0}")]
+    Serialize(String),
+
+    #[error("unsupported manifest format for {0}, expected a .toml or .json file")]
+    UnsupportedFormat(PathBuf),
+
+    #[error("unsupported wav file {path}: {reason}")]
+    Wav { path: PathBuf, reason: String },
+
+    #[error("sample slot {slot} is claimed by more than one file: {}", display_paths(.paths))]
+    SlotConflict { slot: u32, paths: Vec<PathBuf> },
+
+    #[error(transparent)]
+    Syro(#[from] SyroError),
+}
+
+fn display_paths(paths: &[PathBuf]) -> String {
+    let names: Vec<_> = paths.iter().map(|p| p.display().to_string()).collect();
+    names.join(", ")
+}
+
+/// A sample slot loaded from a .wav file
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SampleEntry {
+    /// The sample slot, 0-99
+    pub slot: u32,
+    /// Path to the .wav file, relative paths are resolved against the project base directory
+    pub path: PathBuf,
+    /// Compression bit depth, 8-16
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compression: Option<u32>,
+    /// PCM data and sample rate held in memory, used instead of reading
+    /// `path` and written to it when the project is saved
+    #[serde(skip)]
+    pub data: Option<(Vec<i16>, u32)>,
+}
+
+/// A part of a pattern, see [Part] for the available fields
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PartEntry {
+    /// The part index, 0-9
+    pub index: u8,
+    #[serde(flatten)]
+    pub part: Part,
+}
+
+/// A pattern slot built from its parts, parts that are not listed keep their default values
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PatternEntry {
+    /// The pattern slot, 0-9
+    pub slot: usize,
+    #[serde(default)]
+    pub parts: Vec<PartEntry>,
+}
+
+/// A project manifest
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Project {
+    /// Directory used to resolve relative sample paths
+    #[serde(skip)]
+    pub base_dir: PathBuf,
+    /// Sample slots to erase
+    #[serde(default)]
+    pub erase: Vec<u32>,
+    #[serde(default)]
+    pub samples: Vec<SampleEntry>,
+    #[serde(default)]
+    pub patterns: Vec<PatternEntry>,
+}
+
+impl Project {
+    /// Loads a manifest, the format is picked from the file extension
+    ///
+    /// Relative sample paths are resolved against the directory of the manifest.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ProjectError> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path).map_err(|source| ProjectError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let mut project = match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => Self::from_toml(&contents)?,
+            Some("json") => Self::from_json(&contents)?,
+            _ => return Err(ProjectError::UnsupportedFormat(path.to_path_buf())),
+        };
+        if let Some(parent) = path.parent() {
+            project.base_dir = parent.to_path_buf();
+        }
+        Ok(project)
+    }
+
+    /// Parses a TOML manifest, relative sample paths are resolved against the working directory
+    pub fn from_toml(contents: &str) -> Result<Self, ProjectError> {
+        toml::from_str(contents).map_err(|e| ProjectError::Parse(e.to_string()))
+    }
+
+    /// Parses a JSON manifest, relative sample paths are resolved against the working directory
+    pub fn from_json(contents: &str) -> Result<Self, ProjectError> {
+        serde_json::from_str(contents).map_err(|e| ProjectError::Parse(e.to_string()))
+    }
+
+    /// Creates a project from the contents of a syro stream
+    ///
+    /// Samples are held in memory, with paths named after their slot.
+    pub fn from_syro_stream(syro_stream: &SyroStream) -> Result<Self, ProjectError> {
+        let mut project = Self::default();
+
+        for bundle in syro_stream.samples() {
+            let syro_data = bundle.data();
+            let compression = match syro_data.DataType {
+                syro::SyroDataType::DataType_Sample_Erase => {
+                    project.erase.push(syro_data.Number);
+                    continue;
+                }
+                syro::SyroDataType::DataType_Sample_Compress => Some(syro_data.Quality),
+                _ => None,
+            };
+            let mut data = vec![0; bundle.data.len() / 2];
+            LittleEndian::read_i16_into(&bundle.data, &mut data);
+            project.samples.push(SampleEntry {
+                slot: syro_data.Number,
+                path: PathBuf::from(format!("sample_{:02}.wav", syro_data.Number)),
+                compression,
+                data: Some((data, syro_data.Fs)),
+            });
+        }
+
+        for bundle in syro_stream.patterns() {
+            let pattern = Pattern::from_bytes(&bundle.data)?;
+            let parts = pattern
+                .parts()
+                .enumerate()
+                .map(|(index, part)| PartEntry {
+                    index: index as u8,
+                    part,
+                })
+                .collect();
+            project.patterns.push(PatternEntry {
+                slot: bundle.data().Number as usize,
+                parts,
+            });
+        }
+
+        Ok(project)
+    }
+
+    /// Writes the manifest to the given path, the format is picked from the file extension
+    ///
+    /// Samples held in memory are written as .wav files to their path,
+    /// relative to the directory of the manifest.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ProjectError> {
+        let path = path.as_ref();
+        let contents = match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => self.to_toml()?,
+            Some("json") => self.to_json()?,
+            _ => return Err(ProjectError::UnsupportedFormat(path.to_path_buf())),
+        };
+
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+        for entry in self.samples.iter() {
+            if let Some((data, sample_rate)) = &entry.data {
+                write_wav(&dir.join(&entry.path), data, *sample_rate)?;
+            }
+        }
+
+        std::fs::write(path, contents).map_err(|source| ProjectError::Write {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+
+    /// Serializes the manifest as TOML
+    pub fn to_toml(&self) -> Result<String, ProjectError> {
+        // going through a toml::Value puts plain values ahead of tables
+        toml::Value::try_from(self)
+            .and_then(|value| toml::to_string_pretty(&value))
+            .map_err(|e| ProjectError::Serialize(e.to_string()))
+    }
+
+    /// Serializes the manifest as JSON
+    pub fn to_json(&self) -> Result<String, ProjectError> {
+        serde_json::to_string_pretty(self).map_err(|e| ProjectError::Serialize(e.to_string()))
+    }
+
+    /// Reads the sample files and builds the syro stream
+    pub fn to_syro_stream(&self) -> Result<SyroStream, ProjectError> {
+        let mut syro_stream = SyroStream::default();
+
+        for &slot in self.erase.iter() {
+            syro_stream.erase_sample(slot)?;
+        }
+
+        for entry in self.samples.iter() {
+            let (data, sample_rate) = self.sample_data(entry)?;
+            syro_stream.add_sample(entry.slot, data, sample_rate, entry.compression)?;
+        }
+
+        for entry in self.patterns.iter() {
+            syro_stream.add_pattern(entry.slot, entry.to_pattern()?)?;
+        }
+
+        Ok(syro_stream)
+    }
+
+    fn sample_data(&self, entry: &SampleEntry) -> Result<(Vec<i16>, u32), ProjectError> {
+        match &entry.data {
+            Some((data, sample_rate)) => Ok((data.clone(), *sample_rate)),
+            None => read_wav(&self.base_dir.join(&entry.path)),
+        }
+    }
+
+    fn sample_slots(&self) -> Result<BTreeMap<u32, SampleSlot>, ProjectError> {
+        let mut slots = BTreeMap::new();
+        for entry in self.samples.iter() {
+            let (data, sample_rate) = self.sample_data(entry)?;
+            slots.insert(entry.slot, (data, sample_rate, entry.compression));
+        }
+        Ok(slots)
+    }
+
+    fn pattern_slots(&self) -> Result<BTreeMap<usize, Pattern>, ProjectError> {
+        let mut slots = BTreeMap::new();
+        for entry in self.patterns.iter() {
+            slots.insert(entry.slot, entry.to_pattern()?);
+        }
+        Ok(slots)
+    }
+}
+
+// sample data, sample rate and compression of a sample slot
+type SampleSlot = (Vec<i16>, u32, Option<u32>);
+
+impl PatternEntry {
+    fn to_pattern(&self) -> Result<Pattern, ProjectError> {
+        let mut pattern = Pattern::default();
+        for part in self.parts.iter() {
+            pattern.with_part(part.index, part.part)?;
+        }
+        Ok(pattern)
+    }
+}
+
+/// Sample slots picked from the numbers file names start with
+///
+/// Follows the common convention of naming samples after their slot, so
+/// `"07 kick.wav"` goes to slot 7 and `"42_clap.wav"` to slot 42. Building the
+/// mapping does not read the files, so it doubles as a dry run, and its
+/// [Display](std::fmt::Display) output lists the resulting mapping.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SlotMapping {
+    /// Directory the files were found in
+    pub dir: PathBuf,
+    /// File names by sample slot
+    pub slots: BTreeMap<u32, PathBuf>,
+    /// Slots claimed by more than one file, with all of the files
+    pub conflicts: BTreeMap<u32, Vec<PathBuf>>,
+    /// Files without a slot number 0-99 at the start of their name
+    pub unmapped: Vec<PathBuf>,
+}
+
+impl SlotMapping {
+    /// Maps the .wav files in a directory, subdirectories are not visited
+    pub fn from_dir<P: AsRef<Path>>(dir: P) -> Result<Self, ProjectError> {
+        let dir = dir.as_ref();
+        let mut mapping = Self {
+            dir: dir.to_path_buf(),
+            ..Default::default()
+        };
+        for path in wav_paths(dir)? {
+            let name = PathBuf::from(path.file_name().unwrap_or_default());
+            let slot = match slot_from_name(&name) {
+                Some(slot) => slot,
+                None => {
+                    mapping.unmapped.push(name);
+                    continue;
+                }
+            };
+            if let Some(conflict) = mapping.conflicts.get_mut(&slot) {
+                conflict.push(name);
+            } else if let Some(existing) = mapping.slots.remove(&slot) {
+                mapping.conflicts.insert(slot, vec![existing, name]);
+            } else {
+                mapping.slots.insert(slot, name);
+            }
+        }
+        Ok(mapping)
+    }
+
+    /// Builds a project loading every mapped file into its slot
+    ///
+    /// Fails with [ProjectError::SlotConflict] on the first conflict, unmapped
+    /// files are left out.
+    pub fn to_project(&self) -> Result<Project, ProjectError> {
+        if let Some((&slot, paths)) = self.conflicts.iter().next() {
+            return Err(ProjectError::SlotConflict {
+                slot,
+                paths: paths.clone(),
+            });
+        }
+        Ok(Project {
+            base_dir: self.dir.clone(),
+            samples: self
+                .slots
+                .iter()
+                .map(|(&slot, path)| SampleEntry {
+                    slot,
+                    path: path.clone(),
+                    compression: None,
+                    data: None,
+                })
+                .collect(),
+            ..Default::default()
+        })
+    }
+}
+
+impl std::fmt::Display for SlotMapping {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (slot, path) in self.slots.iter() {
+            writeln!(f, "{:>2}  {}", slot, path.display())?;
+        }
+        for (slot, paths) in self.conflicts.iter() {
+            writeln!(f, "{:>2}  conflict: {}", slot, display_paths(paths))?;
+        }
+        for path in self.unmapped.iter() {
+            writeln!(f, "--  {}", path.display())?;
+        }
+        Ok(())
+    }
+}
+
+// the slot number at the start of a file name, 0-99
+fn slot_from_name(name: &Path) -> Option<u32> {
+    let name = name.to_str()?;
+    let digits = name.chars().take_while(char::is_ascii_digit).count();
+    match name[..digits].parse() {
+        Ok(slot) if slot < 100 => Some(slot),
+        _ => None,
+    }
+}
+
+/// Outcome of [add_dir](SyroStream::add_dir)
+#[derive(Debug, Default)]
+pub struct DirLoad {
+    /// Sample slots and the files loaded into them
+    pub loaded: Vec<(u32, PathBuf)>,
+    /// Files that could not be read or did not fit, with the reason
+    pub skipped: Vec<(PathBuf, ProjectError)>,
+}
+
+impl SyroStream {
+    /// Loads every .wav file in a directory into consecutive sample slots,
+    /// starting at `start_index`
+    ///
+    /// Files are loaded in sorted order and subdirectories are not visited.
+    /// The sample rate of each file replaces the one in `options`. Files that
+    /// cannot be read, are too large or are left without a slot are skipped
+    /// and reported in the returned [DirLoad], without using up a slot.
+    pub fn add_dir<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        start_index: u32,
+        options: &AddSampleOptions,
+    ) -> Result<DirLoad, ProjectError> {
+        let mut result = DirLoad::default();
+        let mut index = start_index;
+        for path in wav_paths(path.as_ref())? {
+            let added = read_wav(&path).and_then(|(data, sample_rate)| {
+                if VolcaSample::SAMPLE_SLOTS <= index as usize {
+                    return Err(SyroError::NoFreeSlot.into());
+                }
+                let mut options = *options;
+                options.sample_rate = sample_rate;
+                self.add_sample_with(index, data, &options)?;
+                Ok(())
+            });
+            match added {
+                Ok(()) => {
+                    result.loaded.push((index, path));
+                    index += 1;
+                }
+                Err(err) => result.skipped.push((path, err)),
+            }
+        }
+        Ok(result)
+    }
+
+    /// Builds a stream holding only the changes needed to go from the `old`
+    /// project to the `new` one
+    ///
+    /// Samples that are added or replaced are sent, samples that are no
+    /// longer part of the project or newly listed for erasing are erased, and
+    /// patterns that are added or changed are sent. Removed patterns are
+    /// cleared with [clear_pattern](SyroStream::clear_pattern). The stream is
+    /// empty if nothing changed.
+    pub fn diff(old: &Project, new: &Project) -> Result<SyroStream, ProjectError> {
+        let mut syro_stream = SyroStream::default();
+
+        let old_samples = old.sample_slots()?;
+        let new_samples = new.sample_slots()?;
+        for &slot in old_samples.keys() {
+            if !new_samples.contains_key(&slot) {
+                syro_stream.erase_sample(slot)?;
+            }
+        }
+        for &slot in new.erase.iter() {
+            if !old.erase.contains(&slot) && !new_samples.contains_key(&slot) {
+                syro_stream.erase_sample(slot)?;
+            }
+        }
+        for (&slot, sample) in new_samples.iter() {
+            if old_samples.get(&slot) != Some(sample) {
+                let (data, sample_rate, compression) = sample.clone();
+                syro_stream.add_sample(slot, data, sample_rate, compression)?;
+            }
+        }
+
+        let old_patterns = old.pattern_slots()?;
+        let new_patterns = new.pattern_slots()?;
+        for &slot in old_patterns.keys() {
+            if !new_patterns.contains_key(&slot) {
+                syro_stream.clear_pattern(slot)?;
+            }
+        }
+        for (&slot, pattern) in new_patterns.iter() {
+            if old_patterns.get(&slot) != Some(pattern) {
+                syro_stream.add_pattern(slot, pattern.clone())?;
+            }
+        }
+
+        Ok(syro_stream)
+    }
+}
+
+// the .wav files in a directory, sorted
+fn wav_paths(dir: &Path) -> Result<Vec<PathBuf>, ProjectError> {
+    let io_error = |source| ProjectError::Io {
+        path: dir.to_path_buf(),
+        source,
+    };
+    let mut paths = vec![];
+    for entry in std::fs::read_dir(dir).map_err(io_error)? {
+        let path = entry.map_err(io_error)?.path();
+        let is_wav = matches!(
+            path.extension().and_then(|e| e.to_str()),
+            Some(e) if e.eq_ignore_ascii_case("wav")
+        );
+        if is_wav && path.is_file() {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+/// Reads a .wav file as 16 bit mono PCM, returning the data and sample rate
+///
+/// Multi-channel files are downmixed by averaging the channels.
+pub(crate) fn read_wav(path: &Path) -> Result<(Vec<i16>, u32), ProjectError> {
+    let io_error = |source| ProjectError::Io {
+        path: path.to_path_buf(),
+        source,
+    };
+    let mut file = File::open(path).map_err(io_error)?;
+    let (header, data) = wav::read(&mut file).map_err(io_error)?;
+
+    let data: Vec<i16> = match data {
+        wav::BitDepth::Eight(data) => data.iter().map(|&v| (v as i16 - 128) << 8).collect(),
+        wav::BitDepth::Sixteen(data) => data,
+        wav::BitDepth::TwentyFour(data) => data.iter().map(|&v| (v >> 8) as i16).collect(),
+        _ => {
+            return Err(ProjectError::Wav {
+                path: path.to_path_buf(),
+                reason: format!("{} bit samples", header.bits_per_sample),
+            })
+        }
+    };
+
+    let channels = header.channel_count.max(1) as usize;
+    let data = data
+        .chunks(channels)
+        .map(|frame| (frame.iter().map(|&v| v as i32).sum::<i32>() / frame.len() as i32) as i16)
+        .collect();
+
+    Ok((data, header.sampling_rate))
+}
+
+/// Writes 16 bit mono PCM data to a .wav file
+pub(crate) fn write_wav(path: &Path, data: &[i16], sample_rate: u32) -> Result<(), ProjectError> {
+    let io_error = |source| ProjectError::Write {
+        path: path.to_path_buf(),
+        source,
+    };
+    let header = wav::Header::new(1, 1, sample_rate, 16);
+    let output = File::create(path).map_err(io_error)?;
+    wav::write(
+        header,
+        &wav::BitDepth::Sixteen(data.to_vec()),
+        &mut BufWriter::new(output),
+    )
+    .map_err(io_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern::*;
+
+    fn write_test_wav(path: &Path, channels: u16, data: Vec<i16>) {
+        let header = wav::Header::new(1, channels, 44100, 16);
+        let output = File::create(path).unwrap();
+        wav::write(
+            header,
+            &wav::BitDepth::Sixteen(data),
+            &mut BufWriter::new(output),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn add_dir() -> anyhow::Result<()> {
+        let dir = std::env::temp_dir().join("korg-syro-add-dir-test");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir)?;
+        write_test_wav(&dir.join("a.wav"), 1, vec![0, 1000, -1000, 0]);
+        std::fs::write(dir.join("b.wav"), b"not a wav file")?;
+        write_test_wav(&dir.join("c.WAV"), 1, vec![0, 500]);
+        write_test_wav(&dir.join("d.wav"), 1, vec![0, 250]);
+        std::fs::write(dir.join("notes.txt"), b"ignored")?;
+
+        let mut syro_stream = SyroStream::default();
+        let load = syro_stream.add_dir(&dir, 98, &AddSampleOptions::default())?;
+        assert_eq!(
+            load.loaded,
+            vec![(98, dir.join("a.wav")), (99, dir.join("c.WAV"))]
+        );
+        let skipped: Vec<_> = load.skipped.iter().map(|(path, _)| path.clone()).collect();
+        assert_eq!(skipped, vec![dir.join("b.wav"), dir.join("d.wav")]);
+        assert!(matches!(
+            load.skipped[1].1,
+            ProjectError::Syro(SyroError::NoFreeSlot)
+        ));
+        assert!(syro_stream.sample(98).is_some() && syro_stream.sample(99).is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn slot_mapping() -> anyhow::Result<()> {
+        let dir = std::env::temp_dir().join("korg-syro-slot-mapping-test");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir)?;
+        for name in [
+            "07 kick.wav",
+            "42_clap.wav",
+            "3-a.wav",
+            "03 b.wav",
+            "hat.wav",
+            "100.wav",
+        ] {
+            write_test_wav(&dir.join(name), 1, vec![0, 1000]);
+        }
+
+        let mapping = SlotMapping::from_dir(&dir)?;
+        let slots: Vec<_> = mapping.slots.keys().copied().collect();
+        assert_eq!(slots, vec![7, 42]);
+        assert_eq!(
+            mapping.conflicts.get(&3),
+            Some(&vec![PathBuf::from("03 b.wav"), PathBuf::from("3-a.wav")])
+        );
+        assert_eq!(
+            mapping.unmapped,
+            vec![PathBuf::from("100.wav"), PathBuf::from("hat.wav")]
+        );
+        assert!(mapping.to_string().contains(" 7  07 kick.wav"));
+        assert!(matches!(
+            mapping.to_project(),
+            Err(ProjectError::SlotConflict { slot: 3, .. })
+        ));
+
+        std::fs::remove_file(dir.join("3-a.wav"))?;
+        let project = SlotMapping::from_dir(&dir)?.to_project()?;
+        let slots: Vec<_> = project.samples.iter().map(|s| s.slot).collect();
+        assert_eq!(slots, vec![3, 7, 42]);
+        project.to_syro_stream()?;
+        Ok(())
+    }
+
+    #[test]
+    fn load_manifest() -> anyhow::Result<()> {
+        let dir = std::env::temp_dir().join("korg-syro-project-test");
+        std::fs::create_dir_all(&dir)?;
+        write_test_wav(&dir.join("kick.wav"), 1, vec![0, 1000, -1000, 0]);
+        write_test_wav(&dir.join("snare.wav"), 2, vec![100, 300, -100, -300]);
+        std::fs::write(
+            dir.join("kit.toml"),
+            r#"
+            erase = [10]
+
+            [[samples]]
+            slot = 0
+            path = "kick.wav"
+
+            [[samples]]
+            slot = 1
+            path = "snare.wav"
+            compression = 12
+
+            [[patterns]]
+            slot = 0
+
+            [[patterns.parts]]
+            index = 0
+            sample = 0
+            steps = "x...x...x...x..."
+            "#,
+        )?;
+
+        let project = Project::load(dir.join("kit.toml"))?;
+        assert_eq!(project.erase, vec![10]);
+        assert_eq!(project.samples[1].compression, Some(12));
+        assert_eq!(
+            project.patterns[0].parts[0].part.to_string(),
+            "000 x...x...x...x..."
+        );
+
+        let (data, sample_rate) = read_wav(&dir.join("snare.wav"))?;
+        assert_eq!(data, vec![200, -200]);
+        assert_eq!(sample_rate, 44100);
+
+        project.to_syro_stream()?.generate()?;
+        Ok(())
+    }
+
+    #[test]
+    fn json_manifest() -> anyhow::Result<()> {
+        let project = Project::from_json(r#"{ "erase": [1, 2] }"#)?;
+        assert_eq!(project.erase, vec![1, 2]);
+        assert!(project.samples.is_empty());
+
+        let dir = std::env::temp_dir().join("korg-syro-manifest-format-test");
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join("kit.yaml");
+        std::fs::write(&path, "erase: [1, 2]\n")?;
+        assert!(matches!(
+            Project::load(&path),
+            Err(ProjectError::UnsupportedFormat(unsupported)) if unsupported == path
+        ));
+        Ok(())
+    }
+
+    fn sample(slot: u32, data: Vec<i16>) -> SampleEntry {
+        SampleEntry {
+            slot,
+            path: PathBuf::from(format!("sample_{:02}.wav", slot)),
+            compression: None,
+            data: Some((data, 44100)),
+        }
+    }
+
+    #[test]
+    fn diff() -> anyhow::Result<()> {
+        let old = Project {
+            erase: vec![20],
+            samples: vec![
+                sample(0, vec![1, 2]),
+                sample(1, vec![3, 4]),
+                sample(2, vec![5, 6]),
+            ],
+            patterns: vec![
+                PatternEntry {
+                    slot: 0,
+                    parts: vec![PartEntry {
+                        index: 0,
+                        part: Part::for_sample(0)?
+                            .with_steps(Steps::from_indices(vec![0])?)
+                            .build(),
+                    }],
+                },
+                PatternEntry {
+                    slot: 1,
+                    parts: vec![],
+                },
+            ],
+            ..Default::default()
+        };
+        let mut new = old.clone();
+        new.erase = vec![20, 30];
+        new.samples.remove(2);
+        new.samples[1] = sample(1, vec![3, 5]);
+        new.samples.push(sample(3, vec![7, 8]));
+        new.patterns[0].parts[0]
+            .part
+            .with_steps(Steps::from_indices(vec![4])?);
+        new.patterns.remove(1);
+
+        let diff = SyroStream::diff(&old, &new)?;
+        let changes = Project::from_syro_stream(&diff)?;
+        assert_eq!(changes.erase, vec![2, 30]);
+        let slots: Vec<u32> = changes.samples.iter().map(|entry| entry.slot).collect();
+        assert_eq!(slots, vec![1, 3]);
+        assert_eq!(changes.samples[0].data, Some((vec![3, 5], 44100)));
+        let slots: Vec<usize> = changes.patterns.iter().map(|entry| entry.slot).collect();
+        assert_eq!(slots, vec![0, 1]);
+
+        let unchanged = SyroStream::diff(&new, &new)?;
+        assert!(matches!(unchanged.generate(), Err(SyroError::EmptyStream)));
+        Ok(())
+    }
+
+    #[test]
+    fn save_round_trip() -> anyhow::Result<()> {
+        let dir = std::env::temp_dir().join("korg-syro-project-save-test");
+        std::fs::create_dir_all(&dir)?;
+
+        let mut pattern = Pattern::default();
+        pattern.with_part(
+            2,
+            Part::for_sample(7)?
+                .with_steps(Steps::from_indices(vec![0, 8])?)
+                .speed(Speed::Semitone(5))?
+                .level_start_motion_seq(*MotionSeq::empty().set(Step::Two, Some(42)))?
+                .build(),
+        )?;
+        let mut syro_stream = SyroStream::default();
+        syro_stream
+            .add_sample(7, vec![0, 1000, -1000, 0], 32000, Some(10))?
+            .erase_sample(8)?
+            .add_pattern(3, pattern.clone())?;
+
+        let project = Project::from_syro_stream(&syro_stream)?;
+        assert_eq!(project.erase, vec![8]);
+        assert_eq!(project.samples[0].compression, Some(10));
+
+        for manifest in ["kit.toml", "kit.json"].iter() {
+            project.save(dir.join(manifest))?;
+            let loaded = Project::load(dir.join(manifest))?;
+            assert_eq!(loaded.erase, vec![8]);
+            assert_eq!(loaded.samples[0].slot, 7);
+            assert_eq!(loaded.samples[0].compression, Some(10));
+            assert_eq!(
+                read_wav(&dir.join(&loaded.samples[0].path))?,
+                (vec![0, 1000, -1000, 0], 32000)
+            );
+            assert_eq!(loaded.patterns[0].slot, 3);
+            let mut restored = Pattern::default();
+            for part in loaded.patterns[0].parts.iter() {
+                restored.with_part(part.index, part.part)?;
+            }
+            assert_eq!(restored.to_string(), pattern.to_string());
+            loaded.to_syro_stream()?.generate()?;
+        }
+        Ok(())
+    }
+}
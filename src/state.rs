@@ -0,0 +1,192 @@
+//! Simulation of the sample and pattern slots of the device
+use crate::{
+    check_sample_index, data_type, rejected, DataType, DeviceProfile, PatternIndex, SyroError,
+    SyroStream, VolcaSample,
+};
+use std::convert::TryFrom;
+
+const SAMPLE_SLOTS: usize = VolcaSample::SAMPLE_SLOTS;
+const PATTERN_SLOTS: usize = VolcaSample::PATTERN_SLOTS;
+
+/// Assumed contents of the device: the size of the sample in every slot and
+/// the patterns that are set
+///
+/// Starts out empty, mark what is known to be on the device with
+/// [occupy](DeviceState::occupy) and [set_pattern](DeviceState::set_pattern),
+/// then [apply](DeviceState::apply) a stream to see what a transfer would
+/// change before playing it back.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeviceState {
+    samples: [Option<usize>; SAMPLE_SLOTS],
+    patterns: [bool; PATTERN_SLOTS],
+}
+
+impl Default for DeviceState {
+    fn default() -> Self {
+        Self {
+            samples: [None; SAMPLE_SLOTS],
+            patterns: [false; PATTERN_SLOTS],
+        }
+    }
+}
+
+/// The outcome of a transfer, see [DeviceState::apply]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Projection {
+    /// Contents of the device after the transfer
+    pub state: DeviceState,
+    /// Sample slots that get a sample
+    pub loaded: Vec<u32>,
+    /// Sample slots whose previous sample is replaced by a new one
+    pub overwritten: Vec<u32>,
+    /// Sample slots whose sample is erased
+    pub erased: Vec<u32>,
+    /// Pattern slots that get a pattern, including overwritten ones
+    pub patterns: Vec<usize>,
+}
+
+impl DeviceState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Marks the slot as holding a sample of `size` bytes of 16 bit PCM data
+    pub fn occupy(&mut self, index: u8, size: usize) -> Result<&mut Self, SyroError> {
+        check_sample_index(index)?;
+        let available = self.free_memory() + self.samples[index as usize].unwrap_or(0);
+        if size > available {
+            return Err(SyroError::InsufficientMemory { size, available });
+        }
+        self.samples[index as usize] = Some(size);
+        Ok(self)
+    }
+
+    /// Marks the pattern slot as holding a pattern
+    pub fn set_pattern(&mut self, index: usize) -> Result<&mut Self, SyroError> {
+        PatternIndex::try_from(index)?;
+        self.patterns[index] = true;
+        Ok(self)
+    }
+
+    /// Returns the size in bytes of the sample in the slot
+    pub fn sample_size(&self, index: u32) -> Option<usize> {
+        *self.samples.get(index as usize)?
+    }
+
+    /// Returns the sample slots holding a sample in order
+    pub fn occupied(&self) -> impl Iterator<Item = u32> + '_ {
+        (0..SAMPLE_SLOTS as u32).filter(move |&index| self.samples[index as usize].is_some())
+    }
+
+    /// Returns true if the pattern slot holds a pattern
+    pub fn has_pattern(&self, index: usize) -> bool {
+        self.patterns.get(index).copied().unwrap_or(false)
+    }
+
+    /// Returns the sample memory in use in bytes
+    pub fn used_memory(&self) -> usize {
+        self.samples.iter().flatten().sum()
+    }
+
+    /// Returns the sample memory left in bytes
+    pub fn free_memory(&self) -> usize {
+        VolcaSample::SAMPLE_MEMORY.saturating_sub(self.used_memory())
+    }
+
+    /// Projects the state of the device after the stream is played back
+    ///
+    /// Items are applied in the order the device receives them, so a sample
+    /// only fits if enough memory is free once the items before it are
+    /// applied. Fails with [SyroError::SlotRejected] for the first sample that
+    /// does not fit, or any item the stream could not be generated with.
+    pub fn apply(&self, stream: &SyroStream) -> Result<Projection, SyroError> {
+        let mut projection = Projection {
+            state: self.clone(),
+            loaded: vec![],
+            overwritten: vec![],
+            erased: vec![],
+            patterns: vec![],
+        };
+        let state = &mut projection.state;
+        for item in stream.syro_data()?.iter() {
+            let index = item.Number;
+            match data_type(item.DataType) {
+                DataType::Sample | DataType::CompressedSample => {
+                    let replaced = state.samples[index as usize];
+                    state
+                        .occupy(index as u8, item.Size as usize)
+                        .map_err(|err| rejected(item, err))?;
+                    projection.loaded.push(index);
+                    if replaced.is_some() {
+                        projection.overwritten.push(index);
+                    }
+                }
+                DataType::SampleErase => {
+                    if state.samples[index as usize].take().is_some() {
+                        projection.erased.push(index);
+                    }
+                }
+                DataType::Pattern => {
+                    state.patterns[index as usize] = true;
+                    projection.patterns.push(index as usize);
+                }
+                // replaced by SyroStream::reset, never queued in a stream
+                DataType::AllData | DataType::CompressedAllData => {}
+            }
+        }
+        Ok(projection)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{pattern::Pattern, SlotId, MAX_SAMPLE_SIZE};
+
+    #[test]
+    fn apply() -> anyhow::Result<()> {
+        let mut state = DeviceState::new();
+        state
+            .occupy(0, 1000)?
+            .occupy(1, 2000)?
+            .occupy(2, MAX_SAMPLE_SIZE - 4000)?;
+        assert_eq!(state.free_memory(), 1000);
+
+        let mut syro_stream = SyroStream::default();
+        syro_stream
+            .add_sample(0, vec![0; 1000], 44100, None)?
+            .erase_sample(1)?
+            .erase_sample(3)?
+            .add_sample(4, vec![0; 1000], 44100, Some(8))?
+            .add_pattern(0, Pattern::default())?;
+        let projection = state.apply(&syro_stream)?;
+        assert_eq!(projection.loaded, [0, 4]);
+        assert_eq!(projection.overwritten, [0]);
+        assert_eq!(projection.erased, [1]);
+        assert_eq!(projection.patterns, [0]);
+        assert_eq!(projection.state.occupied().collect::<Vec<_>>(), [0, 2, 4]);
+        assert_eq!(projection.state.sample_size(0), Some(2000));
+        assert_eq!(projection.state.free_memory(), 0);
+        assert!(projection.state.has_pattern(0));
+        // the state itself is left as is
+        assert_eq!(state.sample_size(1), Some(2000));
+
+        // the erase comes after the load, so the memory is not free yet
+        let mut syro_stream = SyroStream::default();
+        syro_stream
+            .add_sample(0, vec![0; 1500], 44100, None)?
+            .erase_sample(2)?;
+        assert_eq!(
+            state.apply(&syro_stream),
+            Err(SyroError::SlotRejected {
+                slot: SlotId::Sample(0),
+                data_type: DataType::Sample,
+                source: Box::new(SyroError::InsufficientMemory {
+                    size: 3000,
+                    available: 2000,
+                }),
+            })
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,324 @@
+//! Checking generated streams by decoding them again
+use byteorder::{ByteOrder, LittleEndian};
+use korg_syro_sys as syro;
+
+use crate::decode::{self, DecodedItem};
+use crate::{OutputRate, SlotId, SyroDataBundle, SyroError, SyroStream, OUTPUT_SAMPLE_RATE};
+
+// uncompressed head of an .alldata file
+const ALL_INFO_SIZE: usize = 0x4000;
+
+/// The outcome of [generate_verified](SyroStream::generate_verified)
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VerificationReport {
+    /// Number of queued items the stream carries intact
+    pub verified: usize,
+    /// Differences between the queued items and the decoded stream
+    pub mismatches: Vec<Mismatch>,
+}
+
+impl VerificationReport {
+    /// Returns true when the stream carries all queued items intact
+    pub fn is_ok(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+/// A difference found by [generate_verified](SyroStream::generate_verified),
+/// the slot is None for .alldata
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Mismatch {
+    /// A queued item is missing from the stream
+    Missing { slot: Option<SlotId> },
+    /// The stream carries an item that was not queued
+    Unexpected { slot: Option<SlotId> },
+    /// The data of an item differs from the offset on, counted in samples
+    /// for samples and in bytes otherwise
+    Data { slot: Option<SlotId>, offset: usize },
+    /// A sample is tagged with another sample rate than queued
+    SampleRate {
+        slot: SlotId,
+        queued: u32,
+        decoded: u32,
+    },
+}
+
+impl SyroStream {
+    /// Generates the syro stream and decodes it again to check that it
+    /// carries the queued data
+    ///
+    /// Compressed samples are expected at the reduced bit depth. Fails with
+    /// [SyroError::Undecodable] if the stream can't be decoded at all. With an
+    /// [output_rate](SyroStream::output_rate) other than [OUTPUT_SAMPLE_RATE]
+    /// the stream is rendered a second time at that rate for checking, which
+    /// leaves the conversion to the output rate unchecked.
+    pub fn generate_verified(self) -> Result<(Vec<i16>, VerificationReport), SyroError> {
+        let queued: Vec<DecodedItem> = self.slots.values().map(expected).collect();
+        let (stream, decoded) = if self.output_rate.hz() == OUTPUT_SAMPLE_RATE {
+            let stream = self.generate()?;
+            let items = decode::decode(&stream);
+            (stream, items)
+        } else {
+            let mut native = self.clone();
+            native.output_rate = OutputRate::default();
+            let items = decode::decode(&native.generate()?);
+            (self.generate()?, items)
+        };
+        let decoded = decoded.map_err(|source| SyroError::Undecodable { source })?;
+        Ok((stream, verify(&queued, &decoded)))
+    }
+}
+
+// the item a bundle should decode to
+fn expected(bundle: &SyroDataBundle) -> DecodedItem {
+    let item = bundle.syro_data;
+    let index = item.Number as u8;
+    let pcm = |data: &[u8]| {
+        let mut pcm = vec![0; data.len() / 2];
+        LittleEndian::read_i16_into(&data[..pcm.len() * 2], &mut pcm);
+        pcm
+    };
+    match item.DataType {
+        syro::SyroDataType::DataType_Sample_Liner => DecodedItem::Sample {
+            index,
+            data: pcm(&bundle.data),
+            sample_rate: item.Fs,
+            compression: None,
+        },
+        syro::SyroDataType::DataType_Sample_Compress => DecodedItem::Sample {
+            index,
+            data: quantize(pcm(&bundle.data), item.Quality),
+            sample_rate: item.Fs,
+            compression: Some(item.Quality),
+        },
+        syro::SyroDataType::DataType_Sample_Erase => DecodedItem::Erase { index },
+        syro::SyroDataType::DataType_Pattern => DecodedItem::Pattern {
+            index,
+            data: bundle.data.clone(),
+        },
+        syro::SyroDataType::DataType_Sample_All => DecodedItem::AllData {
+            data: bundle.data.clone(),
+            compression: None,
+        },
+        syro::SyroDataType::DataType_Sample_AllCompress => {
+            let mut data = bundle.data.clone();
+            if data.len() > ALL_INFO_SIZE {
+                let samples = quantize(pcm(&data[ALL_INFO_SIZE..]), item.Quality);
+                LittleEndian::write_i16_into(
+                    &samples,
+                    &mut data[ALL_INFO_SIZE..ALL_INFO_SIZE + samples.len() * 2],
+                );
+            }
+            DecodedItem::AllData {
+                data,
+                compression: Some(item.Quality),
+            }
+        }
+    }
+}
+
+// reduces the samples to the bit depth the way the compression does
+fn quantize(mut data: Vec<i16>, bit_depth: u32) -> Vec<i16> {
+    let step = 1 << (16 - bit_depth.clamp(8, 16));
+    for value in data.iter_mut() {
+        *value = *value / step * step;
+    }
+    data
+}
+
+// whether both are the same kind of item for the same slot
+fn same_item(a: &DecodedItem, b: &DecodedItem) -> bool {
+    let compression = |item: &DecodedItem| match item {
+        DecodedItem::Sample { compression, .. } | DecodedItem::AllData { compression, .. } => {
+            *compression
+        }
+        _ => None,
+    };
+    std::mem::discriminant(a) == std::mem::discriminant(b)
+        && a.slot() == b.slot()
+        && compression(a) == compression(b)
+}
+
+// offset of the first difference, including one of the lengths
+fn first_difference<T: PartialEq>(a: &[T], b: &[T]) -> Option<usize> {
+    a.iter()
+        .zip(b)
+        .position(|(a, b)| a != b)
+        .or_else(|| (a.len() != b.len()).then(|| a.len().min(b.len())))
+}
+
+// difference between items that passed same_item
+fn compare(queued: &DecodedItem, decoded: &DecodedItem) -> Option<Mismatch> {
+    let slot = queued.slot();
+    let offset = match (queued, decoded) {
+        (
+            DecodedItem::Sample {
+                index,
+                data,
+                sample_rate,
+                ..
+            },
+            DecodedItem::Sample {
+                data: decoded_data,
+                sample_rate: decoded_rate,
+                ..
+            },
+        ) => {
+            // the stream only holds the rate up to the precision of the speed
+            // field, which the decoder can't always restore
+            if decode::speed(*sample_rate) != decode::speed(*decoded_rate) {
+                return Some(Mismatch::SampleRate {
+                    slot: SlotId::Sample(*index),
+                    queued: *sample_rate,
+                    decoded: *decoded_rate,
+                });
+            }
+            first_difference(data, decoded_data)
+        }
+        (DecodedItem::Pattern { data, .. }, DecodedItem::Pattern { data: decoded, .. })
+        | (DecodedItem::AllData { data, .. }, DecodedItem::AllData { data: decoded, .. }) => {
+            first_difference(data, decoded)
+        }
+        _ => None,
+    };
+    offset.map(|offset| Mismatch::Data { slot, offset })
+}
+
+// matches the decoded items against the queued ones, both in transfer order
+fn verify(queued: &[DecodedItem], decoded: &[DecodedItem]) -> VerificationReport {
+    let mut report = VerificationReport::default();
+    let mut next = 0;
+    for item in decoded {
+        match queued[next..]
+            .iter()
+            .position(|queued| same_item(queued, item))
+        {
+            Some(skipped) => {
+                for missing in &queued[next..next + skipped] {
+                    report.mismatches.push(Mismatch::Missing {
+                        slot: missing.slot(),
+                    });
+                }
+                next += skipped;
+                match compare(&queued[next], item) {
+                    Some(mismatch) => report.mismatches.push(mismatch),
+                    None => report.verified += 1,
+                }
+                next += 1;
+            }
+            None => report
+                .mismatches
+                .push(Mismatch::Unexpected { slot: item.slot() }),
+        }
+    }
+    for missing in &queued[next..] {
+        report.mismatches.push(Mismatch::Missing {
+            slot: missing.slot(),
+        });
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern::Pattern;
+
+    fn sine(len: usize) -> Vec<i16> {
+        (0..len)
+            .map(|i| ((i as f32 * 0.03).sin() * 15000.0) as i16)
+            .collect()
+    }
+
+    fn stream() -> anyhow::Result<SyroStream> {
+        let mut syro_stream = SyroStream::default();
+        syro_stream
+            .add_sample(0, sine(4000), 31250, None)?
+            .add_sample(1, sine(4000), 22050, Some(10))?
+            .erase_sample(2)?
+            .add_pattern(0, Pattern::default())?;
+        Ok(syro_stream)
+    }
+
+    #[test]
+    fn generate_verified() -> anyhow::Result<()> {
+        let (data, report) = stream()?.generate_verified()?;
+        assert_eq!(data, stream()?.generate()?);
+        assert_eq!(
+            report,
+            VerificationReport {
+                verified: 4,
+                mismatches: vec![],
+            }
+        );
+        assert!(report.is_ok());
+
+        let mut syro_stream = stream()?;
+        syro_stream.output_rate(OutputRate::Hz48000)?;
+        let (data, report) = syro_stream.clone().generate_verified()?;
+        assert_eq!(data, syro_stream.generate()?);
+        assert_eq!(report.verified, 4);
+        assert!(report.is_ok());
+
+        let (_, report) = SyroStream::from_alldata(vec![7; ALL_INFO_SIZE + 100], Some(12))?
+            .generate_verified()?;
+        assert!(report.is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn mismatches() -> anyhow::Result<()> {
+        let queued: Vec<DecodedItem> = stream()?.slots.values().map(expected).collect();
+        assert_eq!(verify(&queued, &queued).verified, 4);
+
+        let mut decoded = queued.clone();
+        decoded.remove(2);
+        if let DecodedItem::Sample {
+            data, sample_rate, ..
+        } = &mut decoded[0]
+        {
+            data[100] += 1;
+            *sample_rate = 32000;
+        }
+        if let DecodedItem::Sample { data, .. } = &mut decoded[1] {
+            data.truncate(3000);
+        }
+        decoded.push(DecodedItem::Erase { index: 50 });
+        assert_eq!(
+            verify(&queued, &decoded),
+            VerificationReport {
+                verified: 1,
+                mismatches: vec![
+                    Mismatch::SampleRate {
+                        slot: SlotId::Sample(0),
+                        queued: 31250,
+                        decoded: 32000,
+                    },
+                    Mismatch::Data {
+                        slot: Some(SlotId::Sample(1)),
+                        offset: 3000,
+                    },
+                    Mismatch::Missing {
+                        slot: Some(SlotId::Sample(2)),
+                    },
+                    Mismatch::Unexpected {
+                        slot: Some(SlotId::Sample(50)),
+                    },
+                ],
+            }
+        );
+
+        if let DecodedItem::Sample { sample_rate, .. } = &mut decoded[0] {
+            *sample_rate = 31250;
+        }
+        assert_eq!(
+            compare(&queued[0], &decoded[0]),
+            Some(Mismatch::Data {
+                slot: Some(SlotId::Sample(0)),
+                offset: 100,
+            })
+        );
+        assert_eq!(quantize(vec![-300, 300, 255], 8), vec![-256, 256, 0]);
+        Ok(())
+    }
+}